@@ -0,0 +1,219 @@
+use anyhow::{bail, Context, Result};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Component, Path};
+
+use crate::frontmatter::{self, SkillMeta};
+use crate::lockfile;
+use crate::registry;
+
+/// Name of the manifest stored as the first entry of a skill archive.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Describes a packed skill: metadata from its SKILL.md frontmatter and the
+/// hash of every file, so the archive can be validated without a registry.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SkillManifest {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_tools: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    /// Same digest the lockfile records for the installed skill.
+    pub hash: String,
+    /// Relative path → sha256.
+    pub files: BTreeMap<String, String>,
+}
+
+/// Tar header for a file with a fixed timestamp, so packing is reproducible.
+fn header(size: u64, mode: u32) -> tar::Header {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(size);
+    header.set_mode(mode);
+    header.set_mtime(0);
+    header.set_entry_type(tar::EntryType::Regular);
+    header
+}
+
+#[cfg(unix)]
+fn file_mode(path: &Path) -> Result<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    let executable = fs::metadata(path)?.permissions().mode() & 0o111 != 0;
+    Ok(if executable { 0o755 } else { 0o644 })
+}
+
+#[cfg(not(unix))]
+fn file_mode(_path: &Path) -> Result<u32> {
+    Ok(0o644)
+}
+
+/// Pack the skill directory `src` into a `.tar.gz` at `out`.
+///
+/// The archive holds `manifest.json` followed by `<name>/<file>` for every
+/// file except `.gitkeep`, the same set `install` copies.
+pub fn pack_skill(src: &Path, name: &str, out: &Path) -> Result<SkillManifest> {
+    let skill_md = src.join("SKILL.md");
+    if !skill_md.is_file() {
+        bail!("Skill '{}' has no SKILL.md", name);
+    }
+    let meta: SkillMeta = frontmatter::read(&skill_md)?.unwrap_or_default();
+    let files = lockfile::hash_tree(src)?;
+    let manifest = SkillManifest {
+        name: name.to_string(),
+        description: meta.description,
+        tags: meta.tags,
+        allowed_tools: meta.allowed_tools,
+        version: meta.version,
+        license: meta.license,
+        hash: lockfile::content_hash(&files),
+        files,
+    };
+
+    let file = File::create(out).with_context(|| format!("Failed to create {}", out.display()))?;
+    let mut tar = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    let json = format!("{}\n", serde_json::to_string_pretty(&manifest)?);
+    tar.append_data(&mut header(json.len() as u64, 0o644), MANIFEST_FILE, json.as_bytes())?;
+    for rel in manifest.files.keys() {
+        let path = src.join(rel);
+        let bytes = fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let mut h = header(bytes.len() as u64, file_mode(&path)?);
+        tar.append_data(&mut h, format!("{}/{}", name, rel), bytes.as_slice())?;
+    }
+    tar.into_inner()?.finish()?;
+    Ok(manifest)
+}
+
+/// Unpack a skill archive into `<root>/claude/skills/<name>` and validate it.
+///
+/// Every entry must be a regular file listed in the manifest, inside the
+/// skill's directory, with the hash the manifest records.
+pub fn unpack_skill(archive: &Path, root: &Path) -> Result<SkillManifest> {
+    let file = File::open(archive).with_context(|| format!("Failed to open {}", archive.display()))?;
+    let mut tar = tar::Archive::new(GzDecoder::new(file));
+    let mut entries = tar
+        .entries()
+        .with_context(|| format!("Failed to read {}", archive.display()))?;
+
+    let mut manifest: SkillManifest = match entries.next() {
+        Some(entry) => {
+            let mut entry = entry?;
+            if entry.path()?.as_os_str() != MANIFEST_FILE {
+                bail!("{} does not start with {}", archive.display(), MANIFEST_FILE);
+            }
+            let mut json = String::new();
+            entry.read_to_string(&mut json)?;
+            serde_json::from_str(&json)
+                .with_context(|| format!("Invalid {} in {}", MANIFEST_FILE, archive.display()))?
+        }
+        None => bail!("{} is empty", archive.display()),
+    };
+    if manifest.name.is_empty() || manifest.name.contains(['/', '\\']) || manifest.name.starts_with('.') {
+        bail!("Invalid skill name '{}' in {}", manifest.name, archive.display());
+    }
+
+    let skill_dir = registry::skills_dir(root).join(&manifest.name);
+    let mut unpacked = BTreeMap::new();
+    for entry in entries {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        let rel = path
+            .strip_prefix(&manifest.name)
+            .ok()
+            .filter(|r| r.components().all(|c| matches!(c, Component::Normal(_))))
+            .with_context(|| format!("{} is outside the skill directory", path.display()))?;
+        let rel = rel
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        if entry.header().entry_type() != tar::EntryType::Regular {
+            bail!("{} is not a regular file", path.display());
+        }
+        let Some(expected) = manifest.files.get(&rel) else {
+            bail!("{} is not listed in {}", path.display(), MANIFEST_FILE);
+        };
+        let target = skill_dir.join(&rel);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        entry.unpack(&target)?;
+        let actual = lockfile::hash_file(&target)?;
+        if &actual != expected {
+            bail!("{} does not match the hash in {}", path.display(), MANIFEST_FILE);
+        }
+        unpacked.insert(rel, actual);
+    }
+
+    let missing: Vec<&String> = manifest.files.keys().filter(|f| !unpacked.contains_key(*f)).collect();
+    if !missing.is_empty() {
+        bail!(
+            "{} lists files missing from the archive: {}",
+            MANIFEST_FILE,
+            missing.iter().map(|s| s.as_str()).collect::<Vec<_>>().join(", ")
+        );
+    }
+    if lockfile::content_hash(&unpacked) != manifest.hash {
+        bail!("{} hash does not match its files", MANIFEST_FILE);
+    }
+    manifest.files = unpacked;
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn pack_and_unpack_round_trip() {
+        let tmp = TempDir::new().unwrap();
+        let src = tmp.path().join("review");
+        fs::create_dir_all(src.join("references")).unwrap();
+        fs::write(src.join("SKILL.md"), "---\nname: review\ndescription: Reviews\ntags: [a]\n---\n").unwrap();
+        fs::write(src.join("references/guide.md"), "guide").unwrap();
+        fs::write(src.join(".gitkeep"), "").unwrap();
+
+        let out = tmp.path().join("review.tar.gz");
+        let packed = pack_skill(&src, "review", &out).unwrap();
+        assert_eq!(packed.tags, vec!["a"]);
+        assert_eq!(packed.files.len(), 2);
+        // Reproducible: packing again yields the same bytes
+        let again = tmp.path().join("again.tar.gz");
+        pack_skill(&src, "review", &again).unwrap();
+        assert_eq!(fs::read(&out).unwrap(), fs::read(&again).unwrap());
+
+        let root = tmp.path().join("unpacked");
+        let manifest = unpack_skill(&out, &root).unwrap();
+        assert_eq!(manifest.name, "review");
+        assert_eq!(manifest.hash, packed.hash);
+        let dir = registry::skills_dir(&root).join("review");
+        assert_eq!(fs::read_to_string(dir.join("references/guide.md")).unwrap(), "guide");
+        assert!(!dir.join(".gitkeep").exists());
+    }
+
+    #[test]
+    fn unpack_rejects_tampered_archives() {
+        let tmp = TempDir::new().unwrap();
+        let out = tmp.path().join("evil.tar.gz");
+        let manifest = r#"{"name": "evil", "hash": "x", "files": {"SKILL.md": "0000"}}"#;
+        let mut tar = tar::Builder::new(GzEncoder::new(File::create(&out).unwrap(), Compression::default()));
+        tar.append_data(&mut header(manifest.len() as u64, 0o644), MANIFEST_FILE, manifest.as_bytes())
+            .unwrap();
+        tar.append_data(&mut header(5, 0o644), "evil/SKILL.md", &b"hello"[..]).unwrap();
+        tar.into_inner().unwrap().finish().unwrap();
+
+        let err = unpack_skill(&out, &tmp.path().join("unpacked")).unwrap_err();
+        assert!(err.to_string().contains("does not match"), "{}", err);
+    }
+}
@@ -0,0 +1,148 @@
+use anyhow::{bail, Context, Result};
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+use crate::catalog_builder::{AgentEntry, Catalog, SkillEntry};
+use crate::lockfile::ItemKind;
+use crate::registry;
+
+/// File name of the catalog `catalog build` writes for `kind`.
+pub fn file_name(kind: ItemKind) -> &'static str {
+    match kind {
+        ItemKind::Skill => "skill-catalog.json",
+        ItemKind::Agent => "agent-catalog.json",
+        ItemKind::Hook => "hook-catalog.json",
+        ItemKind::Mcp => "mcp-catalog.json",
+    }
+}
+
+/// An item of a published catalog: the files the catalog lists for it and
+/// the hashes every installed copy must match.
+#[derive(Debug)]
+pub struct CatalogItem {
+    /// Registry root the catalog describes (the directory holding the catalog file).
+    pub root: PathBuf,
+    /// What the lockfile records as the item's source.
+    pub source: PathBuf,
+    /// The catalog or archive whose detached signature covers `files`.
+    pub signed: PathBuf,
+    /// Name the item is installed under (skill directory or agent file stem).
+    pub name: String,
+    /// Relative path → expected sha256.
+    pub files: BTreeMap<String, String>,
+}
+
+/// True if `rel` stays inside the directory it is relative to.
+fn is_contained(rel: &str) -> bool {
+    !rel.is_empty() && Path::new(rel).components().all(|c| matches!(c, Component::Normal(_)))
+}
+
+fn load<T: DeserializeOwned>(path: &Path) -> Result<(PathBuf, Catalog<T>)> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read catalog {}", path.display()))?;
+    let catalog = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse catalog {}", path.display()))?;
+    let root = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."))
+        .to_path_buf();
+    Ok((root, catalog))
+}
+
+/// Look up skill `name` (its directory or declared name) in a skill catalog.
+pub fn find_skill(path: &Path, name: &str) -> Result<CatalogItem> {
+    let (root, catalog) = load::<SkillEntry>(path)?;
+    let Some(entry) = catalog.items.into_iter().find(|e| e.dir == name || e.name == name) else {
+        bail!("skill '{}' not found in catalog", name);
+    };
+    if !is_contained(&entry.dir) || entry.dir.contains(['/', '\\']) {
+        bail!("skill '{}' has invalid directory '{}' in catalog", name, entry.dir);
+    }
+    for file in &entry.files {
+        if !is_contained(file) {
+            bail!("skill '{}' lists a path outside its directory: {}", name, file);
+        }
+        if !entry.file_hashes.contains_key(file) {
+            bail!(
+                "skill '{}' has no hash for {} in catalog (rebuild it with `catalog build`)",
+                name,
+                file
+            );
+        }
+    }
+    let files = entry
+        .file_hashes
+        .into_iter()
+        .filter(|(f, _)| entry.files.contains(f))
+        .collect();
+    Ok(CatalogItem {
+        source: root.clone(),
+        signed: path.to_path_buf(),
+        root,
+        name: entry.dir,
+        files,
+    })
+}
+
+/// Look up agent `name` (its file stem or declared name) in an agent catalog.
+pub fn find_agent(path: &Path, name: &str) -> Result<CatalogItem> {
+    let (root, catalog) = load::<AgentEntry>(path)?;
+    let Some(entry) = catalog
+        .items
+        .into_iter()
+        .find(|e| registry::file_stem(Path::new(&e.file)) == name || e.name == name)
+    else {
+        bail!("agent '{}' not found in catalog", name);
+    };
+    if !is_contained(&entry.file) || entry.file.contains(['/', '\\']) || !entry.file.ends_with(".md") {
+        bail!("agent '{}' has invalid file '{}' in catalog", name, entry.file);
+    }
+    if entry.file_hash.is_empty() {
+        bail!(
+            "agent '{}' has no file hash in catalog (rebuild it with `catalog build`)",
+            name
+        );
+    }
+    Ok(CatalogItem {
+        source: root.clone(),
+        signed: path.to_path_buf(),
+        root,
+        name: registry::file_stem(Path::new(&entry.file)),
+        files: BTreeMap::from([(entry.file, entry.file_hash)]),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn finds_entries_and_rejects_escaping_paths() {
+        let tmp = TempDir::new().unwrap();
+        let catalog = tmp.path().join("skill-catalog.json");
+        fs::write(
+            &catalog,
+            r#"{"version": "x", "items": [
+                {"name": "review", "dir": "code-review", "files": ["SKILL.md"],
+                 "file_hashes": {"SKILL.md": "abc"}},
+                {"name": "evil", "dir": "evil", "files": ["../../.bashrc"],
+                 "file_hashes": {"../../.bashrc": "abc"}},
+                {"name": "old", "dir": "old", "files": ["SKILL.md"]}
+            ]}"#,
+        )
+        .unwrap();
+
+        let item = find_skill(&catalog, "review").unwrap();
+        assert_eq!(item.root, tmp.path());
+        assert_eq!(item.name, "code-review");
+        assert_eq!(item.files["SKILL.md"], "abc");
+
+        assert!(find_skill(&catalog, "evil").is_err());
+        assert!(find_skill(&catalog, "old").unwrap_err().to_string().contains("no hash"));
+        assert!(find_skill(&catalog, "missing").is_err());
+    }
+}
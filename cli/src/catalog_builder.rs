@@ -1,57 +1,93 @@
-use anyhow::Result;
-use serde::Serialize;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::Path;
 use walkdir::WalkDir;
 
-use crate::frontmatter::parse_frontmatter;
+use crate::frontmatter::{self, AgentMeta, SkillMeta};
+use crate::lockfile::{self, ItemKind};
+use crate::mcp;
 use crate::registry;
 
-#[derive(Serialize)]
-pub struct SkillCatalog {
+/// A generated catalog. Output depends only on registry content, so
+/// rebuilding an unchanged registry reproduces the same file.
+#[derive(Serialize, Deserialize)]
+pub struct Catalog<T> {
+    /// Digest of the item hashes; changes exactly when some item changes.
     pub version: String,
-    pub generated_at: String,
-    pub items: Vec<SkillEntry>,
+    /// Only set by `catalog build --timestamp`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generated_at: Option<String>,
+    pub items: Vec<T>,
 }
 
-#[derive(Serialize)]
+pub type SkillCatalog = Catalog<SkillEntry>;
+pub type AgentCatalog = Catalog<AgentEntry>;
+pub type HookCatalog = Catalog<HookEntry>;
+pub type McpCatalog = Catalog<McpEntry>;
+pub type ProfileCatalog = Catalog<ProfileEntry>;
+
+impl<T> Catalog<T> {
+    fn new(items: Vec<T>, hash: impl Fn(&T) -> &str) -> Self {
+        let hashes: Vec<&str> = items.iter().map(hash).collect();
+        Catalog {
+            version: lockfile::hash_bytes(hashes.join("\n").as_bytes())[..12].to_string(),
+            generated_at: None,
+            items,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct SkillEntry {
     pub name: String,
     pub dir: String,
+    /// Same digest the lockfile records and registry.toml pins.
+    #[serde(default)]
+    pub hash: String,
+    #[serde(default)]
     pub description: String,
     pub files: Vec<String>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    /// Relative path → sha256 of each file in `files`.
+    #[serde(default)]
+    pub file_hashes: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_tools: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
 }
 
-#[derive(Serialize)]
-pub struct AgentCatalog {
-    pub version: String,
-    pub generated_at: String,
-    pub items: Vec<AgentEntry>,
-}
-
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 pub struct AgentEntry {
     pub name: String,
     pub file: String,
+    #[serde(default)]
+    pub hash: String,
+    /// sha256 of `file`.
+    #[serde(default)]
+    pub file_hash: String,
+    #[serde(default)]
     pub description: String,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub tags: Vec<String>,
-    #[serde(skip_serializing_if = "String::is_empty")]
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub tools: String,
-    #[serde(skip_serializing_if = "String::is_empty")]
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub model: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
 }
 
 pub fn build_skill_catalog(root: &Path) -> Result<SkillCatalog> {
-    let now = chrono::Utc::now().to_rfc3339();
     let skill_dirs = registry::list_skills(root)?;
     let mut items = Vec::new();
 
     for skill_dir in skill_dirs {
-        let skill_md = skill_dir.join("SKILL.md");
-        let content = std::fs::read_to_string(&skill_md)?;
-        let fm = parse_frontmatter(&content);
+        let meta: SkillMeta = frontmatter::read(&skill_dir.join("SKILL.md"))?.unwrap_or_default();
 
         let dir_name = skill_dir
             .file_name()
@@ -59,51 +95,34 @@ pub fn build_skill_catalog(root: &Path) -> Result<SkillCatalog> {
             .to_string_lossy()
             .to_string();
 
-        let name = fm
-            .get("name")
-            .cloned()
-            .unwrap_or_else(|| dir_name.clone());
-        let description = fm.get("description").cloned().unwrap_or_default();
-        let tags = fm
-            .get("tags")
-            .map(|t| t.split(',').map(|s| s.trim().to_string()).collect())
-            .unwrap_or_default();
-
-        // Collect files excluding .gitkeep
-        let mut files = Vec::new();
-        for entry in WalkDir::new(&skill_dir) {
-            let entry = entry?;
-            if entry.file_type().is_file() && entry.file_name() != ".gitkeep" {
-                let rel = entry.path().strip_prefix(&skill_dir)?;
-                files.push(rel.to_string_lossy().to_string());
-            }
-        }
-        files.sort();
+        let name = meta.name.unwrap_or_else(|| dir_name.clone());
+
+        // Every file excluding .gitkeep, with its hash
+        let file_hashes = lockfile::hash_tree(&skill_dir)?;
 
         items.push(SkillEntry {
             name,
             dir: dir_name,
-            description,
-            files,
-            tags,
+            hash: lockfile::content_hash(&file_hashes),
+            description: meta.description,
+            files: file_hashes.keys().cloned().collect(),
+            file_hashes,
+            tags: meta.tags,
+            allowed_tools: meta.allowed_tools,
+            version: meta.version,
+            license: meta.license,
         });
     }
 
-    Ok(SkillCatalog {
-        version: "1.0.0".to_string(),
-        generated_at: now,
-        items,
-    })
+    Ok(Catalog::new(items, |e: &SkillEntry| &e.hash))
 }
 
 pub fn build_agent_catalog(root: &Path) -> Result<AgentCatalog> {
-    let now = chrono::Utc::now().to_rfc3339();
     let agent_files = registry::list_agents(root)?;
     let mut items = Vec::new();
 
     for agent_file in agent_files {
-        let content = std::fs::read_to_string(&agent_file)?;
-        let fm = parse_frontmatter(&content);
+        let meta: AgentMeta = frontmatter::read(&agent_file)?.unwrap_or_default();
 
         let file_name = agent_file
             .file_name()
@@ -116,28 +135,314 @@ pub fn build_agent_catalog(root: &Path) -> Result<AgentCatalog> {
             .to_string_lossy()
             .to_string();
 
-        let name = fm.get("name").cloned().unwrap_or_else(|| stem.clone());
-        let description = fm.get("description").cloned().unwrap_or_default();
-        let tags = fm
-            .get("tags")
-            .map(|t| t.split(',').map(|s| s.trim().to_string()).collect())
-            .unwrap_or_default();
-        let tools = fm.get("tools").cloned().unwrap_or_default();
-        let model = fm.get("model").cloned().unwrap_or_default();
-
         items.push(AgentEntry {
-            name,
+            name: meta.name.unwrap_or(stem),
             file: file_name,
-            description,
-            tags,
-            tools,
-            model,
+            hash: lockfile::content_hash(&lockfile::hash_single(&agent_file)?),
+            file_hash: lockfile::hash_file(&agent_file)?,
+            description: meta.description,
+            tags: meta.tags,
+            tools: meta.tools.join(", "),
+            model: meta.model.unwrap_or_default(),
+            version: meta.version,
         });
     }
 
-    Ok(AgentCatalog {
-        version: "1.0.0".to_string(),
-        generated_at: now,
-        items,
-    })
+    Ok(Catalog::new(items, |e: &AgentEntry| &e.hash))
+}
+
+#[derive(Serialize)]
+pub struct HookEntry {
+    pub name: String,
+    pub dir: String,
+    pub hash: String,
+    pub description: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    pub events: Vec<String>,
+    /// Non-empty matchers per event; events matching everything are omitted.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub matchers: BTreeMap<String, Vec<String>>,
+    /// Files shipped in the hook directory that its commands refer to.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub scripts: Vec<String>,
+    /// Parameters that can be set with `hook install --set`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub parameters: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct McpEntry {
+    pub name: String,
+    pub dir: String,
+    pub hash: String,
+    pub description: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Server name → `stdio <command>` or `<type> <url>`.
+    pub servers: BTreeMap<String, String>,
+    /// Variables that can be given with `mcp install --env`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub variables: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct ProfileEntry {
+    pub name: String,
+    pub file: String,
+    pub hash: String,
+    pub description: String,
+    pub skills: Vec<String>,
+    pub agents: Vec<String>,
+    /// Members that exist in neither this registry nor as `source/name`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unresolved: Vec<String>,
+}
+
+/// The parts of HOOK.json the catalog describes.
+#[derive(Deserialize)]
+struct HookFile {
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    tags: String,
+    #[serde(default)]
+    parameters: BTreeMap<String, serde::de::IgnoredAny>,
+    #[serde(default)]
+    hooks: BTreeMap<String, Vec<HookGroup>>,
+}
+
+#[derive(Deserialize)]
+struct HookGroup {
+    #[serde(default)]
+    matcher: String,
+    #[serde(default)]
+    hooks: Vec<HookCommand>,
+}
+
+#[derive(Deserialize)]
+struct HookCommand {
+    #[serde(default)]
+    command: String,
+}
+
+pub fn build_hook_catalog(root: &Path) -> Result<HookCatalog> {
+    let hook_dirs = registry::list_hooks(root)?;
+    let mut items = Vec::new();
+
+    for hook_dir in hook_dirs {
+        let hook_json = hook_dir.join("HOOK.json");
+        let content = std::fs::read_to_string(&hook_json)?;
+        let def: HookFile = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", hook_json.display()))?;
+
+        let commands: Vec<&str> = def
+            .hooks
+            .values()
+            .flatten()
+            .flat_map(|g| &g.hooks)
+            .map(|h| h.command.as_str())
+            .collect();
+        let mut scripts = Vec::new();
+        for entry in WalkDir::new(&hook_dir) {
+            let entry = entry?;
+            if !entry.file_type().is_file() || entry.path() == hook_json {
+                continue;
+            }
+            let rel = entry.path().strip_prefix(&hook_dir)?.to_string_lossy().to_string();
+            if commands.iter().any(|c| c.contains(&rel)) {
+                scripts.push(rel);
+            }
+        }
+        scripts.sort();
+
+        let matchers = def
+            .hooks
+            .iter()
+            .map(|(event, groups)| {
+                let m: Vec<String> = groups
+                    .iter()
+                    .filter(|g| !g.matcher.is_empty())
+                    .map(|g| g.matcher.clone())
+                    .collect();
+                (event.clone(), m)
+            })
+            .filter(|(_, m)| !m.is_empty())
+            .collect();
+
+        items.push(HookEntry {
+            name: def.name,
+            dir: registry::dir_name(&hook_dir),
+            hash: lockfile::content_hash(&lockfile::hash_hook(&hook_dir)?),
+            description: def.description,
+            tags: def
+                .tags
+                .split(',')
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .collect(),
+            events: def.hooks.keys().cloned().collect(),
+            matchers,
+            scripts,
+            parameters: def.parameters.into_keys().collect(),
+        });
+    }
+
+    Ok(Catalog::new(items, |e: &HookEntry| &e.hash))
+}
+
+/// The parts of MCP.json the catalog describes.
+#[derive(Deserialize)]
+struct McpFile {
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    tags: String,
+    #[serde(default)]
+    variables: BTreeMap<String, serde::de::IgnoredAny>,
+    #[serde(default, rename = "mcpServers")]
+    servers: BTreeMap<String, serde_json::Value>,
+}
+
+pub fn build_mcp_catalog(root: &Path) -> Result<McpCatalog> {
+    let mut items = Vec::new();
+
+    for dir in registry::list_mcp(root)? {
+        let mcp_json = dir.join("MCP.json");
+        let content = std::fs::read_to_string(&mcp_json)?;
+        let def: McpFile = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", mcp_json.display()))?;
+
+        items.push(McpEntry {
+            name: def.name,
+            dir: registry::dir_name(&dir),
+            hash: lockfile::content_hash(&lockfile::hash_tree(&dir)?),
+            description: def.description,
+            tags: def
+                .tags
+                .split(',')
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .collect(),
+            servers: def
+                .servers
+                .iter()
+                .map(|(name, server)| (name.clone(), mcp::describe(server)))
+                .collect(),
+            variables: def.variables.into_keys().collect(),
+        });
+    }
+
+    Ok(Catalog::new(items, |e: &McpEntry| &e.hash))
+}
+
+/// Build the profile catalog. Unqualified members must exist in `root`;
+/// qualified `source/name` members refer to other sources and are kept as is.
+pub fn build_profile_catalog(root: &Path) -> Result<ProfileCatalog> {
+    let profile_files = registry::list_profiles(root)?;
+    let mut items = Vec::new();
+
+    for profile_file in profile_files {
+        let profile = registry::read_profile(&profile_file)?;
+        let mut unresolved = Vec::new();
+        let mut resolve = |kind: ItemKind, names: Vec<String>| -> Vec<String> {
+            let mut resolved: Vec<String> = Vec::new();
+            for name in names {
+                if resolved.contains(&name) {
+                    continue;
+                }
+                if name.contains('/') || registry::item_path(root, kind, &name).exists() {
+                    resolved.push(name);
+                } else {
+                    unresolved.push(format!("{}:{}", kind, name));
+                }
+            }
+            resolved
+        };
+        let skills = resolve(ItemKind::Skill, profile.skills);
+        let agents = resolve(ItemKind::Agent, profile.agents);
+
+        items.push(ProfileEntry {
+            name: profile.name,
+            file: registry::dir_name(&profile_file),
+            hash: lockfile::content_hash(&lockfile::hash_single(&profile_file)?),
+            description: profile.description,
+            skills,
+            agents,
+            unresolved,
+        });
+    }
+
+    Ok(Catalog::new(items, |e: &ProfileEntry| &e.hash))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn hook_and_profile_catalogs_describe_registry_content() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        let hook = root.join("claude/hooks/guard");
+        fs::create_dir_all(hook.join("scripts")).unwrap();
+        fs::write(hook.join("scripts/check.sh"), "#!/bin/sh\n").unwrap();
+        fs::write(hook.join("scripts/unused.sh"), "#!/bin/sh\n").unwrap();
+        fs::write(
+            hook.join("HOOK.json"),
+            r#"{"name": "guard", "description": "Guards", "tags": "safety, bash",
+                "hooks": {
+                  "PreToolUse": [{"matcher": "Bash", "hooks": [{"type": "command", "command": "${HOOK_DIR}/scripts/check.sh"}]}],
+                  "Stop": [{"matcher": "", "hooks": [{"type": "command", "command": "true"}]}]
+                }}"#,
+        )
+        .unwrap();
+        fs::create_dir_all(root.join("claude/skills/review")).unwrap();
+        fs::write(root.join("claude/skills/review/SKILL.md"), "---\nname: review\n---\n").unwrap();
+        fs::create_dir_all(root.join("profiles")).unwrap();
+        fs::write(
+            root.join("profiles/dev.json"),
+            r#"{"name": "dev", "skills": ["review", "review", "team/lint", "gone"]}"#,
+        )
+        .unwrap();
+
+        let hooks = build_hook_catalog(root).unwrap();
+        let guard = &hooks.items[0];
+        assert_eq!(guard.tags, vec!["safety", "bash"]);
+        assert_eq!(guard.events, vec!["PreToolUse", "Stop"]);
+        assert_eq!(guard.matchers.get("PreToolUse"), Some(&vec!["Bash".to_string()]));
+        assert!(!guard.matchers.contains_key("Stop"));
+        assert_eq!(guard.scripts, vec!["scripts/check.sh"]);
+
+        let profiles = build_profile_catalog(root).unwrap();
+        let dev = &profiles.items[0];
+        assert_eq!(dev.file, "dev.json");
+        assert_eq!(dev.skills, vec!["review", "team/lint"]);
+        assert_eq!(dev.unresolved, vec!["skill:gone"]);
+    }
+
+    #[test]
+    fn catalogs_are_reproducible_and_versioned_by_content() {
+        let tmp = TempDir::new().unwrap();
+        let skill = tmp.path().join("claude/skills/review");
+        fs::create_dir_all(&skill).unwrap();
+        fs::write(skill.join("SKILL.md"), "---\nname: review\n---\n").unwrap();
+
+        let render = || serde_json::to_string(&build_skill_catalog(tmp.path()).unwrap()).unwrap();
+        let first = render();
+        assert_eq!(first, render());
+        assert!(!first.contains("generated_at"));
+
+        fs::write(skill.join("notes.md"), "more").unwrap();
+        let changed = build_skill_catalog(tmp.path()).unwrap();
+        assert_ne!(first, serde_json::to_string(&changed).unwrap());
+        assert_eq!(
+            changed.items[0].hash,
+            lockfile::content_hash(&lockfile::hash_tree(&skill).unwrap())
+        );
+    }
 }
@@ -1,6 +1,10 @@
 use clap::{Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
 
+use crate::installer::Strategy;
+use crate::lockfile::ItemKind;
+use crate::registry::SettingsScope;
+
 #[derive(Parser)]
 #[command(name = "claude-registry", about = "Claude Skills & Agents Registry CLI")]
 pub struct Cli {
@@ -35,6 +39,110 @@ pub enum Commands {
         #[command(subcommand)]
         action: HookAction,
     },
+    /// Manage MCP servers in .mcp.json or ~/.claude.json
+    Mcp {
+        #[command(subcommand)]
+        action: McpAction,
+    },
+    /// Show and restore settings.json snapshots taken before registry changes
+    Settings {
+        #[command(subcommand)]
+        action: SettingsAction,
+    },
+    /// Show permission rules and the registry items that added them
+    Permissions {
+        #[command(subcommand)]
+        action: PermissionsAction,
+    },
+    /// Manage registry sources
+    Source {
+        #[command(subcommand)]
+        action: SourceAction,
+    },
+    /// Update installed skills, agents, hooks and MCP servers to the registry's current version
+    Update {
+        /// Item names to update (default: everything recorded in registry-lock.json)
+        names: Vec<String>,
+        /// Only update items of this kind
+        #[arg(long, value_enum)]
+        kind: Option<ItemKind>,
+        /// Update items installed in ~/.claude
+        #[arg(long, conflicts_with = "target")]
+        global: bool,
+        /// Target project path
+        #[arg(long, conflicts_with = "global")]
+        target: Option<PathBuf>,
+        /// Preview without modifying
+        #[arg(long)]
+        dry_run: bool,
+        /// How to handle files modified locally since installation
+        #[arg(long, value_enum)]
+        strategy: Option<Strategy>,
+        /// Skip items that fail instead of rolling back the whole update
+        #[arg(long)]
+        keep_going: bool,
+        /// Install new content that is not signed by a trusted key (with a warning)
+        #[arg(long)]
+        allow_unsigned: bool,
+        /// Install new content with high-severity audit findings
+        #[arg(long)]
+        accept_risk: bool,
+    },
+    /// Show how .claude/registry.toml differs from what is installed
+    Plan {
+        /// Use ~/.claude/registry.toml
+        #[arg(long, conflicts_with = "target")]
+        global: bool,
+        /// Target project path
+        #[arg(long, conflicts_with = "global")]
+        target: Option<PathBuf>,
+    },
+    /// Install, update and remove items until the target matches .claude/registry.toml
+    Apply {
+        /// Use ~/.claude/registry.toml
+        #[arg(long, conflicts_with = "target")]
+        global: bool,
+        /// Target project path
+        #[arg(long, conflicts_with = "global")]
+        target: Option<PathBuf>,
+        /// Preview without modifying
+        #[arg(long)]
+        dry_run: bool,
+        /// How to handle files modified locally since installation
+        #[arg(long, value_enum)]
+        strategy: Option<Strategy>,
+        /// Skip items that fail instead of rolling back the whole apply
+        #[arg(long)]
+        keep_going: bool,
+        /// Install content not signed by a trusted key (see `trusted_keys` in config.toml)
+        #[arg(long)]
+        allow_unsigned: bool,
+        /// Install items with high-severity audit findings
+        #[arg(long)]
+        accept_risk: bool,
+    },
+    /// Check skills, agents, hooks, MCP servers and profiles in the registry for problems
+    Lint,
+    /// Scan hook and MCP server commands and skill scripts for risky patterns
+    Audit {
+        /// Skill, hook or MCP server to audit (default: all of them in the registry)
+        name: Option<String>,
+        /// Only audit items of this kind
+        #[arg(long, value_enum)]
+        kind: Option<ItemKind>,
+    },
+    /// Write detached ed25519 signatures (<file>.sig) for catalogs or skill archives
+    Sign {
+        /// Files to sign (default: the registry's skill, agent, hook and MCP catalogs)
+        #[arg(conflicts_with = "generate_key")]
+        files: Vec<PathBuf>,
+        /// Signing key (default: signing.key next to config.toml)
+        #[arg(long, value_name = "FILE")]
+        key: Option<PathBuf>,
+        /// Create a new signing key and print its public key
+        #[arg(long)]
+        generate_key: bool,
+    },
     /// Output completion candidates (hidden, used by shell completion scripts)
     #[command(name = "_complete", hide = true)]
     Complete {
@@ -49,6 +157,9 @@ pub enum CompletionType {
     Agents,
     Profiles,
     Hooks,
+    Mcp,
+    Sources,
+    Snapshots,
 }
 
 #[derive(Subcommand)]
@@ -56,20 +167,55 @@ pub enum SkillAction {
     /// Install skills to a target project
     Install {
         /// Skill names to install
-        #[arg(required = true)]
+        #[arg(required_unless_present = "from_archive")]
         names: Vec<String>,
+        /// Install to ~/.claude/skills
+        #[arg(long, conflicts_with = "target")]
+        global: bool,
         /// Target project path
-        #[arg(long)]
-        target: PathBuf,
+        #[arg(long, conflicts_with = "global")]
+        target: Option<PathBuf>,
         /// Preview without copying
         #[arg(long)]
         dry_run: bool,
+        /// How to handle files modified locally since installation
+        #[arg(long, value_enum)]
+        strategy: Option<Strategy>,
+        /// Skip items that fail instead of rolling back the whole install
+        #[arg(long)]
+        keep_going: bool,
+        /// Symlink to the registry source instead of copying
+        #[arg(long, conflicts_with = "strategy")]
+        link: bool,
+        /// Install the files listed in this catalog, verifying their hashes
+        #[arg(long, value_name = "FILE", conflicts_with = "link")]
+        catalog: Option<PathBuf>,
+        /// Install a skill packed with `skill pack`
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["names", "link", "catalog"])]
+        from_archive: Option<PathBuf>,
+        /// Install content not signed by a trusted key (see `trusted_keys` in config.toml)
+        #[arg(long)]
+        allow_unsigned: bool,
+        /// Install items with high-severity audit findings
+        #[arg(long)]
+        accept_risk: bool,
+    },
+    /// Package a skill into a .tar.gz with a manifest of its metadata and file hashes
+    Pack {
+        /// Skill name
+        name: String,
+        /// Archive path (default: ./<name>.tar.gz)
+        #[arg(long, short)]
+        output: Option<PathBuf>,
     },
     /// List installed skills
     List {
+        /// List from ~/.claude/skills
+        #[arg(long, conflicts_with = "target")]
+        global: bool,
         /// Target project path
-        #[arg(long)]
-        target: PathBuf,
+        #[arg(long, conflicts_with = "global")]
+        target: Option<PathBuf>,
     },
     /// Create a new skill from template
     New {
@@ -85,9 +231,12 @@ pub enum SkillAction {
     Uninstall {
         /// Skill name to uninstall
         name: String,
+        /// Uninstall from ~/.claude/skills
+        #[arg(long, conflicts_with = "target")]
+        global: bool,
         /// Target project path
-        #[arg(long)]
-        target: PathBuf,
+        #[arg(long, conflicts_with = "global")]
+        target: Option<PathBuf>,
     },
 }
 
@@ -98,18 +247,39 @@ pub enum AgentAction {
         /// Agent names to install
         #[arg(required = true)]
         names: Vec<String>,
+        /// Install to ~/.claude/agents
+        #[arg(long, conflicts_with = "target")]
+        global: bool,
         /// Target project path
-        #[arg(long)]
-        target: PathBuf,
+        #[arg(long, conflicts_with = "global")]
+        target: Option<PathBuf>,
         /// Preview without copying
         #[arg(long)]
         dry_run: bool,
+        /// How to handle files modified locally since installation
+        #[arg(long, value_enum)]
+        strategy: Option<Strategy>,
+        /// Skip items that fail instead of rolling back the whole install
+        #[arg(long)]
+        keep_going: bool,
+        /// Symlink to the registry source instead of copying
+        #[arg(long, conflicts_with = "strategy")]
+        link: bool,
+        /// Install the files listed in this catalog, verifying their hashes
+        #[arg(long, value_name = "FILE", conflicts_with = "link")]
+        catalog: Option<PathBuf>,
+        /// Install content not signed by a trusted key (see `trusted_keys` in config.toml)
+        #[arg(long)]
+        allow_unsigned: bool,
     },
     /// List installed agents
     List {
+        /// List from ~/.claude/agents
+        #[arg(long, conflicts_with = "target")]
+        global: bool,
         /// Target project path
-        #[arg(long)]
-        target: PathBuf,
+        #[arg(long, conflicts_with = "global")]
+        target: Option<PathBuf>,
     },
     /// Show available agents in registry
     Available,
@@ -117,16 +287,25 @@ pub enum AgentAction {
     Uninstall {
         /// Agent name to uninstall
         name: String,
+        /// Uninstall from ~/.claude/agents
+        #[arg(long, conflicts_with = "target")]
+        global: bool,
         /// Target project path
-        #[arg(long)]
-        target: PathBuf,
+        #[arg(long, conflicts_with = "global")]
+        target: Option<PathBuf>,
     },
 }
 
 #[derive(Subcommand)]
 pub enum CatalogAction {
-    /// Build skill-catalog.json and agent-catalog.json
-    Build,
+    /// Build skill, agent, hook, MCP and profile catalogs
+    Build {
+        /// Record the build time in generated_at (makes output non-reproducible)
+        #[arg(long)]
+        timestamp: bool,
+    },
+    /// Fail if the checked-in catalogs differ from a fresh build
+    Check,
 }
 
 #[derive(Subcommand)]
@@ -141,9 +320,21 @@ pub enum HookAction {
         /// Install to <path>/.claude/settings.json
         #[arg(long, conflicts_with = "global")]
         target: Option<PathBuf>,
+        /// Settings file to use: user, project or local (project and local need --target)
+        #[arg(long, value_enum, conflicts_with = "global")]
+        scope: Option<SettingsScope>,
         /// Preview without modifying
         #[arg(long)]
         dry_run: bool,
+        /// Install content not signed by a trusted key (see `trusted_keys` in config.toml)
+        #[arg(long)]
+        allow_unsigned: bool,
+        /// Install items with high-severity audit findings
+        #[arg(long)]
+        accept_risk: bool,
+        /// Set a parameter declared in HOOK.json (repeatable; kept on update)
+        #[arg(long = "set", value_name = "KEY=VALUE", value_parser = parse_key_value)]
+        set: Vec<(String, String)>,
     },
     /// Uninstall a hook from settings.json
     Uninstall {
@@ -155,6 +346,9 @@ pub enum HookAction {
         /// Uninstall from <path>/.claude/settings.json
         #[arg(long, conflicts_with = "global")]
         target: Option<PathBuf>,
+        /// Settings file to use: user, project or local (project and local need --target)
+        #[arg(long, value_enum, conflicts_with = "global")]
+        scope: Option<SettingsScope>,
     },
     /// List installed hooks
     List {
@@ -164,9 +358,33 @@ pub enum HookAction {
         /// List from <path>/.claude/settings.json
         #[arg(long, conflicts_with = "global")]
         target: Option<PathBuf>,
+        /// Settings file to list: managed, user, project or local
+        #[arg(long, value_enum, conflicts_with = "global")]
+        scope: Option<SettingsScope>,
+        /// Show the hooks of every scope in Claude Code's precedence order
+        /// (the project is --target or the current directory)
+        #[arg(long, conflicts_with_all = ["global", "scope"])]
+        effective: bool,
     },
     /// Show available hooks in registry
     Available,
+    /// Run a hook's commands against a simulated event, as Claude Code would
+    Test {
+        /// Hook name to test
+        name: String,
+        /// Event to simulate (e.g. PreToolUse, Stop)
+        #[arg(long)]
+        event: String,
+        /// JSON file merged into the event payload sent on stdin
+        #[arg(long, value_name = "FILE")]
+        payload: Option<PathBuf>,
+        /// Tool name matched against the matchers of tool events
+        #[arg(long)]
+        tool: Option<String>,
+        /// Set a parameter declared in HOOK.json (repeatable)
+        #[arg(long = "set", value_name = "KEY=VALUE", value_parser = parse_key_value)]
+        set: Vec<(String, String)>,
+    },
     /// Create a new hook from template
     New {
         /// Hook name
@@ -177,19 +395,157 @@ pub enum HookAction {
     },
 }
 
+#[derive(Subcommand)]
+pub enum McpAction {
+    /// Add an MCP definition's servers to .mcp.json (or ~/.claude.json with --global)
+    Install {
+        /// MCP definition name to install
+        name: String,
+        /// Install to ~/.claude.json (user scope)
+        #[arg(long, conflicts_with = "target")]
+        global: bool,
+        /// Install to <path>/.mcp.json (project scope)
+        #[arg(long, conflicts_with = "global")]
+        target: Option<PathBuf>,
+        /// Preview without modifying
+        #[arg(long)]
+        dry_run: bool,
+        /// Install content not signed by a trusted key (see `trusted_keys` in config.toml)
+        #[arg(long)]
+        allow_unsigned: bool,
+        /// Install items with high-severity audit findings
+        #[arg(long)]
+        accept_risk: bool,
+        /// Set a variable declared in MCP.json (repeatable; kept on update)
+        #[arg(long = "env", value_name = "NAME=VALUE", value_parser = parse_key_value)]
+        env: Vec<(String, String)>,
+    },
+    /// Remove an MCP definition's servers
+    Uninstall {
+        /// MCP definition name to uninstall
+        name: String,
+        /// Uninstall from ~/.claude.json
+        #[arg(long, conflicts_with = "target")]
+        global: bool,
+        /// Uninstall from <path>/.mcp.json
+        #[arg(long, conflicts_with = "global")]
+        target: Option<PathBuf>,
+    },
+    /// List MCP servers with the registry items that added them
+    List {
+        /// List from ~/.claude.json
+        #[arg(long, conflicts_with = "target")]
+        global: bool,
+        /// List from <path>/.mcp.json
+        #[arg(long, conflicts_with = "global")]
+        target: Option<PathBuf>,
+    },
+    /// Show available MCP definitions in registry
+    Available,
+}
+
+#[derive(Subcommand)]
+pub enum PermissionsAction {
+    /// List permissions.allow/ask/deny rules with the items that contributed them
+    List {
+        /// List from ~/.claude/settings.json
+        #[arg(long, conflicts_with = "target")]
+        global: bool,
+        /// List from <path>/.claude/settings.json
+        #[arg(long, conflicts_with = "global")]
+        target: Option<PathBuf>,
+        /// Settings file to list: managed, user, project or local
+        #[arg(long, value_enum, conflicts_with = "global")]
+        scope: Option<SettingsScope>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum SettingsAction {
+    /// List snapshots, newest first
+    History {
+        /// Only snapshots of ~/.claude/settings.json
+        #[arg(long, conflicts_with = "target")]
+        global: bool,
+        /// Only snapshots of <path>/.claude/settings.json
+        #[arg(long, conflicts_with = "global")]
+        target: Option<PathBuf>,
+        /// Only snapshots of this scope's settings file
+        #[arg(long, value_enum, conflicts_with = "global")]
+        scope: Option<SettingsScope>,
+    },
+    /// Put a settings file back to a snapshot (the current version is snapshotted first)
+    Restore {
+        /// Snapshot id from `settings history`
+        id: String,
+        /// Show the change without writing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum SourceAction {
+    /// Clone a git registry into the cache and add it as a source
+    Add {
+        /// Source name (qualifies items as <name>/<item>)
+        name: String,
+        /// Git URL (https, ssh, file:// or a local repository path)
+        url: String,
+        /// Branch or tag to check out
+        #[arg(long = "ref")]
+        git_ref: Option<String>,
+    },
+    /// Fetch and fast-forward git sources
+    Update {
+        /// Source names (default: every git source)
+        names: Vec<String>,
+    },
+    /// List sources in precedence order
+    List,
+    /// Remove a source and its cached clone
+    Remove {
+        /// Source name
+        name: String,
+    },
+}
+
 #[derive(Subcommand)]
 pub enum ProfileAction {
     /// Install all skills and agents from a profile
     Install {
         /// Profile name
         name: String,
+        /// Install to ~/.claude/skills and ~/.claude/agents
+        #[arg(long, conflicts_with = "target")]
+        global: bool,
         /// Target project path
-        #[arg(long)]
-        target: PathBuf,
+        #[arg(long, conflicts_with = "global")]
+        target: Option<PathBuf>,
         /// Preview without copying
         #[arg(long)]
         dry_run: bool,
+        /// How to handle files modified locally since installation
+        #[arg(long, value_enum)]
+        strategy: Option<Strategy>,
+        /// Skip items that fail instead of rolling back the whole install
+        #[arg(long)]
+        keep_going: bool,
+        /// Install content not signed by a trusted key (see `trusted_keys` in config.toml)
+        #[arg(long)]
+        allow_unsigned: bool,
+        /// Install items with high-severity audit findings
+        #[arg(long)]
+        accept_risk: bool,
     },
     /// List available profiles
     List,
 }
+
+/// Parse a `KEY=VALUE` argument.
+fn parse_key_value(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("expected KEY=VALUE, got '{}'", arg)),
+    }
+}
@@ -1,29 +1,60 @@
 use anyhow::Result;
 use std::path::Path;
 
+use crate::catalog;
 use crate::cli::AgentAction;
-use crate::frontmatter::parse_frontmatter;
-use crate::installer;
-use crate::registry;
+use crate::frontmatter::{self, AgentMeta};
+use crate::installer::{self, InstallOptions};
+use crate::lockfile::{self, ItemKind, Lockfile};
+use crate::registry::{self, Registry};
+use crate::transaction;
 
 pub fn run(action: AgentAction) -> Result<()> {
-    let root = registry::resolve_root()?;
+    let reg = Registry::load()?;
 
     match action {
         AgentAction::Install {
             names,
+            global,
             target,
             dry_run,
-        } => install(&root, &names, &target, dry_run),
-        AgentAction::List { target } => list(&target),
-        AgentAction::Available => available(&root),
-        AgentAction::Uninstall { name, target } => uninstall(&root, &name, &target),
+            strategy,
+            keep_going,
+            link,
+            catalog,
+            allow_unsigned,
+        } => {
+            let opts = InstallOptions {
+                strategy,
+                dry_run,
+                link,
+                allow_unsigned,
+                accept_risk: false,
+            };
+            let claude_dir = registry::claude_dir(global, target.as_deref())?;
+            install(&reg, &names, catalog.as_deref(), &claude_dir, &opts, keep_going)
+        }
+        AgentAction::List { global, target } => {
+            list(&registry::claude_dir(global, target.as_deref())?)
+        }
+        AgentAction::Available => available(&reg),
+        AgentAction::Uninstall {
+            name,
+            global,
+            target,
+        } => uninstall(&name, &registry::claude_dir(global, target.as_deref())?),
     }
 }
 
-fn install(root: &Path, names: &[String], target: &Path, dry_run: bool) -> Result<()> {
-    let agents_dir = registry::agents_dir(root);
-    let target_agents = target.join(".claude").join("agents");
+fn install(
+    reg: &Registry,
+    names: &[String],
+    catalog: Option<&Path>,
+    claude_dir: &Path,
+    opts: &InstallOptions,
+    keep_going: bool,
+) -> Result<()> {
+    let target_agents = claude_dir.join("agents");
 
     println!(
         "Installing {} agent(s) to {}",
@@ -31,19 +62,20 @@ fn install(root: &Path, names: &[String], target: &Path, dry_run: bool) -> Resul
         target_agents.display()
     );
 
-    if !dry_run {
+    if !opts.dry_run {
         std::fs::create_dir_all(&target_agents)?;
     }
 
-    for name in names {
-        let src = agents_dir.join(format!("{}.md", name));
-        let dst = target_agents.join(format!("{}.md", name));
-        if let Err(e) = installer::install_agent(&src, &dst, dry_run) {
-            eprintln!("  Warning: {}", e);
+    transaction::apply_all(names, keep_going, |tx, name| {
+        if let Some(catalog) = catalog {
+            let item = catalog::find_agent(catalog, name)?;
+            return installer::sync_catalog_agent(tx, &item, claude_dir, opts);
         }
-    }
+        let (source, name) = reg.resolve(ItemKind::Agent, name)?;
+        installer::sync_agent(tx, &source.root, &name, claude_dir, opts)
+    })?;
 
-    if dry_run {
+    if opts.dry_run {
         println!("\nDry run complete. No files were copied.");
     } else {
         println!("\nDone! Agents installed to {}", target_agents.display());
@@ -51,38 +83,54 @@ fn install(root: &Path, names: &[String], target: &Path, dry_run: bool) -> Resul
     Ok(())
 }
 
-fn list(target: &Path) -> Result<()> {
-    let target_agents = target.join(".claude").join("agents");
-    if !target_agents.is_dir() {
+fn list(claude_dir: &Path) -> Result<()> {
+    let target_agents = claude_dir.join("agents");
+    let lock = Lockfile::load(claude_dir)?;
+    if !target_agents.is_dir() && lock.entries(ItemKind::Agent).next().is_none() {
         println!("No agents installed at {}", target_agents.display());
         return Ok(());
     }
 
-    println!("Installed agents in {}:", target_agents.display());
-    for entry in std::fs::read_dir(&target_agents)? {
-        let entry = entry?;
-        let path = entry.path();
-        if path.is_file() && path.extension().map_or(false, |e| e == "md") {
-            let name = path.file_stem().unwrap_or_default().to_string_lossy();
-            println!("  {}", name);
+    let mut names = Vec::new();
+    if target_agents.is_dir() {
+        for entry in std::fs::read_dir(&target_agents)? {
+            let entry = entry?;
+            let path = entry.path();
+            let is_md = path.extension().is_some_and(|e| e == "md");
+            if is_md && (path.is_file() || transaction::is_symlink(&path)) {
+                names.push(path.file_stem().unwrap_or_default().to_string_lossy().to_string());
+            }
         }
     }
+    for entry in lock.entries(ItemKind::Agent) {
+        if !names.contains(&entry.name) {
+            names.push(entry.name.clone());
+        }
+    }
+    names.sort();
+
+    println!("Installed agents in {}:", target_agents.display());
+    for name in &names {
+        let path = target_agents.join(format!("{}.md", name));
+        let status = lockfile::describe_installed(lock.get(ItemKind::Agent, name), &path);
+        println!("  {:<24} {}", name, status);
+    }
     Ok(())
 }
 
-fn available(root: &Path) -> Result<()> {
-    let agent_files = registry::list_agents(root)?;
+fn available(reg: &Registry) -> Result<()> {
+    let agents = reg.list(registry::list_agents, registry::file_stem)?;
 
     println!("Available Agents:");
     println!();
-    for agent_file in &agent_files {
-        let name = agent_file
-            .file_stem()
-            .unwrap_or_default()
-            .to_string_lossy();
-        let content = std::fs::read_to_string(agent_file)?;
-        let fm = parse_frontmatter(&content);
-        let desc = fm.get("description").cloned().unwrap_or_default();
+    for agent in &agents {
+        let desc = match frontmatter::read::<AgentMeta>(&agent.path) {
+            Ok(meta) => frontmatter::one_line(&meta.unwrap_or_default().description),
+            Err(e) => {
+                eprintln!("  Warning: {:#}", e);
+                String::new()
+            }
+        };
         // Truncate long descriptions for display (char-boundary safe)
         let desc_short: String = if desc.chars().count() > 80 {
             let mut s: String = desc.chars().take(77).collect();
@@ -91,19 +139,18 @@ fn available(root: &Path) -> Result<()> {
         } else {
             desc
         };
-        println!("  {:<24} {}", name, desc_short);
+        println!("{}", agent.row(reg, &desc_short));
     }
 
-    if agent_files.is_empty() {
+    if agents.is_empty() {
         println!("  (none)");
     }
     Ok(())
 }
 
-fn uninstall(_root: &Path, name: &str, target: &Path) -> Result<()> {
-    let target_path = target
-        .join(".claude")
-        .join("agents")
-        .join(format!("{}.md", name));
-    installer::uninstall_agent(&target_path)
+fn uninstall(name: &str, claude_dir: &Path) -> Result<()> {
+    let target_path = claude_dir.join("agents").join(format!("{}.md", name));
+    installer::uninstall_agent(&target_path)?;
+    installer::remove_permissions(None, claude_dir, ItemKind::Agent, name)?;
+    lockfile::forget(claude_dir, ItemKind::Agent, name)
 }
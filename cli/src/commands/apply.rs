@@ -0,0 +1,59 @@
+use anyhow::Result;
+use std::path::Path;
+
+use crate::installer::{self, InstallOptions, Strategy};
+use crate::lockfile::ItemKind;
+use crate::manifest::{self, Action, Change, Manifest};
+use crate::registry::Registry;
+use crate::transaction;
+
+pub fn run(
+    claude_dir: &Path,
+    dry_run: bool,
+    strategy: Option<Strategy>,
+    keep_going: bool,
+    allow_unsigned: bool,
+    accept_risk: bool,
+) -> Result<()> {
+    let reg = Registry::load()?;
+    let manifest = Manifest::load(claude_dir)?;
+    let changes = manifest::plan(&reg, claude_dir, &manifest)?;
+
+    println!(
+        "Applying {} to {}:",
+        manifest::MANIFEST_FILE,
+        claude_dir.display()
+    );
+    if manifest::print_plan(&changes) == 0 {
+        println!("Nothing to do.");
+        return Ok(());
+    }
+    println!();
+
+    let opts = InstallOptions {
+        strategy,
+        dry_run,
+        link: false,
+        allow_unsigned,
+        accept_risk,
+    };
+    let pending: Vec<&Change> = changes
+        .iter()
+        .filter(|c| c.action != Action::Unchanged)
+        .collect();
+    // Removals are the only changes without a registry root
+    transaction::apply_all(&pending, keep_going, |tx, c| match (&c.root, c.kind) {
+        (None, kind) => installer::remove_item(tx, claude_dir, kind, &c.name, dry_run),
+        (Some(root), ItemKind::Skill) => installer::sync_skill(tx, root, &c.name, claude_dir, &opts),
+        (Some(root), ItemKind::Agent) => installer::sync_agent(tx, root, &c.name, claude_dir, &opts),
+        (Some(root), ItemKind::Hook) => installer::sync_hook(tx, root, &c.name, claude_dir, &opts),
+        (Some(root), ItemKind::Mcp) => installer::sync_mcp(tx, root, &c.name, claude_dir, &opts),
+    })?;
+
+    if dry_run {
+        println!("\nDry run complete. No files were modified.");
+    } else {
+        println!("\nDone! {} matches {}", claude_dir.display(), manifest::MANIFEST_FILE);
+    }
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use anyhow::{bail, Result};
+use std::path::PathBuf;
+
+use crate::audit;
+use crate::lockfile::ItemKind;
+use crate::registry::{self, Registry};
+
+pub fn run(name: Option<&str>, kind: Option<ItemKind>) -> Result<()> {
+    let reg = Registry::load()?;
+    let kinds: Vec<ItemKind> = [ItemKind::Skill, ItemKind::Hook, ItemKind::Mcp]
+        .into_iter()
+        .filter(|k| kind.is_none_or(|kind| kind == *k))
+        .collect();
+
+    // (kind, display name, registry root, bare name)
+    let mut items: Vec<(ItemKind, String, PathBuf, String)> = Vec::new();
+    match name {
+        Some(name) => {
+            for &k in &kinds {
+                if let Ok((source, bare)) = reg.resolve(k, name) {
+                    items.push((k, name.to_string(), source.root.clone(), bare));
+                }
+            }
+            if items.is_empty() {
+                bail!("No skill, hook or MCP server named '{}' in the registry", name);
+            }
+        }
+        None => {
+            for &k in &kinds {
+                let listed = match k {
+                    ItemKind::Hook => reg.list(registry::list_hooks, registry::dir_name)?,
+                    ItemKind::Mcp => reg.list(registry::list_mcp, registry::dir_name)?,
+                    _ => reg.list(registry::list_skills, registry::dir_name)?,
+                };
+                for item in listed {
+                    let bare = registry::dir_name(&item.path);
+                    items.push((k, item.name, item.source.root.clone(), bare));
+                }
+            }
+        }
+    }
+
+    println!("Auditing {} item(s):", items.len());
+    let mut counts = [0usize; 3];
+    let mut flagged = 0;
+    for (kind, name, root, bare) in &items {
+        let found = audit::audit_item(root, *kind, bare)?;
+        if found.is_empty() {
+            continue;
+        }
+        flagged += 1;
+        println!("  {} {} ({}):", kind, name, root.display());
+        for finding in &found {
+            counts[finding.severity as usize] += 1;
+            println!("    {}", finding);
+        }
+    }
+
+    let [low, medium, high] = counts;
+    if flagged == 0 {
+        println!("  No findings.");
+        return Ok(());
+    }
+    println!(
+        "\n{} finding(s) in {} item(s): {} high, {} medium, {} low",
+        low + medium + high,
+        flagged,
+        high,
+        medium,
+        low
+    );
+    if high > 0 {
+        bail!(
+            "{} high-severity finding(s); installing these items requires --accept-risk",
+            high
+        );
+    }
+    Ok(())
+}
@@ -1,39 +1,113 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
+use serde::Serialize;
+use std::path::Path;
 
-use crate::catalog_builder;
+use crate::catalog;
+use crate::catalog_builder::{self, Catalog};
 use crate::cli::CatalogAction;
+use crate::lockfile::ItemKind;
 use crate::registry;
 
 pub fn run(action: CatalogAction) -> Result<()> {
     match action {
-        CatalogAction::Build => build(),
+        CatalogAction::Build { timestamp } => build(timestamp),
+        CatalogAction::Check => check(),
     }
 }
 
-fn build() -> Result<()> {
+/// A rendered catalog file.
+struct Rendered {
+    file: &'static str,
+    kind: &'static str,
+    count: usize,
+    json: String,
+}
+
+fn render<T: Serialize>(
+    file: &'static str,
+    kind: &'static str,
+    mut catalog: Catalog<T>,
+    timestamp: Option<&str>,
+) -> Result<Rendered> {
+    catalog.generated_at = timestamp.map(str::to_string);
+    Ok(Rendered {
+        file,
+        kind,
+        count: catalog.items.len(),
+        json: format!("{}\n", serde_json::to_string_pretty(&catalog)?),
+    })
+}
+
+/// Build every catalog for the registry at `root`.
+fn render_all(root: &Path, timestamp: Option<&str>) -> Result<Vec<Rendered>> {
+    Ok(vec![
+        render(catalog::file_name(ItemKind::Skill), "skill", catalog_builder::build_skill_catalog(root)?, timestamp)?,
+        render(catalog::file_name(ItemKind::Agent), "agent", catalog_builder::build_agent_catalog(root)?, timestamp)?,
+        render(catalog::file_name(ItemKind::Hook), "hook", catalog_builder::build_hook_catalog(root)?, timestamp)?,
+        render(catalog::file_name(ItemKind::Mcp), "MCP server", catalog_builder::build_mcp_catalog(root)?, timestamp)?,
+        render("profile-catalog.json", "profile", catalog_builder::build_profile_catalog(root)?, timestamp)?,
+    ])
+}
+
+fn build(timestamp: bool) -> Result<()> {
+    let root = registry::resolve_root()?;
+    let now = timestamp.then(|| chrono::Utc::now().to_rfc3339());
+
+    for catalog in render_all(&root, now.as_deref())? {
+        let path = root.join(catalog.file);
+        std::fs::write(&path, &catalog.json)?;
+        println!(
+            "Generated {} with {} {}(s)",
+            path.display(),
+            catalog.count,
+            catalog.kind
+        );
+    }
+    Ok(())
+}
+
+/// Drop the optional `generated_at` line so timestamped catalogs compare by content.
+fn without_timestamp(json: &str) -> String {
+    json.split_inclusive('\n')
+        .filter(|line| !line.starts_with("  \"generated_at\":"))
+        .collect()
+}
+
+fn check() -> Result<()> {
     let root = registry::resolve_root()?;
+    let mut stale = 0;
 
-    // Build skill catalog
-    let skill_catalog = catalog_builder::build_skill_catalog(&root)?;
-    let skill_path = root.join("skill-catalog.json");
-    let skill_json = serde_json::to_string_pretty(&skill_catalog)?;
-    std::fs::write(&skill_path, format!("{}\n", skill_json))?;
-    println!(
-        "Generated {} with {} skill(s)",
-        skill_path.display(),
-        skill_catalog.items.len()
-    );
-
-    // Build agent catalog
-    let agent_catalog = catalog_builder::build_agent_catalog(&root)?;
-    let agent_path = root.join("agent-catalog.json");
-    let agent_json = serde_json::to_string_pretty(&agent_catalog)?;
-    std::fs::write(&agent_path, format!("{}\n", agent_json))?;
-    println!(
-        "Generated {} with {} agent(s)",
-        agent_path.display(),
-        agent_catalog.items.len()
-    );
+    for catalog in render_all(&root, None)? {
+        let path = root.join(catalog.file);
+        let current = match std::fs::read_to_string(&path) {
+            Ok(content) => without_timestamp(&content),
+            Err(_) => {
+                println!("  {} is missing", catalog.file);
+                stale += 1;
+                continue;
+            }
+        };
+        if current == catalog.json {
+            println!("  {} is up to date", catalog.file);
+            continue;
+        }
+        stale += 1;
+        println!("  {} is out of date:", catalog.file);
+        let patch = diffy::DiffOptions::new()
+            .set_original_filename(format!("a/{}", catalog.file))
+            .set_modified_filename(format!("b/{}", catalog.file))
+            .create_patch(&current, &catalog.json)
+            .to_string();
+        for line in patch.lines() {
+            println!("    {}", line);
+        }
+    }
 
+    if stale > 0 {
+        bail!(
+            "{} catalog(s) out of date; run `claude-registry catalog build`",
+            stale
+        );
+    }
     Ok(())
 }
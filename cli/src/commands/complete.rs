@@ -2,40 +2,33 @@
 use anyhow::Result;
 
 use crate::cli::CompletionType;
-use crate::registry;
+use crate::registry::{self, Registry};
+use crate::settings_history;
 
 pub fn run(completion_type: CompletionType) -> Result<()> {
-    let root = registry::resolve_root()?;
+    let reg = Registry::load()?;
 
-    match completion_type {
-        CompletionType::Skills => {
-            for path in registry::list_skills(&root)? {
-                if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                    println!("{}", name);
-                }
+    let items = match completion_type {
+        CompletionType::Skills => reg.list(registry::list_skills, registry::dir_name)?,
+        CompletionType::Agents => reg.list(registry::list_agents, registry::file_stem)?,
+        CompletionType::Profiles => reg.list(registry::list_profiles, registry::file_stem)?,
+        CompletionType::Hooks => reg.list(registry::list_hooks, registry::dir_name)?,
+        CompletionType::Mcp => reg.list(registry::list_mcp, registry::dir_name)?,
+        CompletionType::Sources => {
+            for source in &reg.sources {
+                println!("{}", source.name);
             }
+            return Ok(());
         }
-        CompletionType::Agents => {
-            for path in registry::list_agents(&root)? {
-                if let Some(name) = path.file_stem().and_then(|n| n.to_str()) {
-                    println!("{}", name);
-                }
-            }
-        }
-        CompletionType::Profiles => {
-            for path in registry::list_profiles(&root)? {
-                if let Some(name) = path.file_stem().and_then(|n| n.to_str()) {
-                    println!("{}", name);
-                }
-            }
-        }
-        CompletionType::Hooks => {
-            for path in registry::list_hooks(&root)? {
-                if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                    println!("{}", name);
-                }
+        CompletionType::Snapshots => {
+            for snapshot in settings_history::list(None)? {
+                println!("{}", snapshot.id);
             }
+            return Ok(());
         }
+    };
+    for item in &items {
+        println!("{}", item.name);
     }
 
     Ok(())
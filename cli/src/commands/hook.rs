@@ -1,66 +1,135 @@
-use anyhow::{bail, Result};
-use std::path::Path;
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use crate::cli::HookAction;
-use crate::installer;
-use crate::registry;
+use crate::hook_params;
+use crate::hook_runner;
+use crate::installer::{self, InstallOptions};
+use crate::lint;
+use crate::lockfile::{self, ItemKind, Lockfile};
+use crate::registry::{self, Registry, SettingsScope};
+use crate::transaction;
 
 pub fn run(action: HookAction) -> Result<()> {
-    let root = registry::resolve_root()?;
+    let reg = Registry::load()?;
 
     match action {
         HookAction::Install {
             name,
             global,
             target,
+            scope,
             dry_run,
-        } => install(&root, &name, global, target.as_deref(), dry_run),
+            allow_unsigned,
+            accept_risk,
+            set,
+        } => {
+            let opts = InstallOptions {
+                dry_run,
+                allow_unsigned,
+                accept_risk,
+                ..Default::default()
+            };
+            let settings = writable_settings(scope, global, target.as_deref())?;
+            install(&reg, &name, &settings, &set, &opts)
+        }
         HookAction::Uninstall {
             name,
             global,
             target,
-        } => uninstall(&name, global, target.as_deref()),
-        HookAction::List { global, target } => list(global, target.as_deref()),
-        HookAction::Available => available(&root),
-        HookAction::New { name, description } => new_hook(&root, &name, description),
+            scope,
+        } => uninstall(&name, &writable_settings(scope, global, target.as_deref())?),
+        HookAction::List {
+            global,
+            target,
+            scope,
+            effective,
+        } => match (effective, scope) {
+            (true, _) => list_effective(target.as_deref()),
+            (false, Some(SettingsScope::Managed)) => {
+                println!("Hooks in managed settings:");
+                list_scopes(&[(SettingsScope::Managed, registry::managed_settings_path()?)])
+            }
+            (false, _) => list(&registry::scoped_settings_path(scope, global, target.as_deref())?),
+        },
+        HookAction::Available => available(&reg),
+        HookAction::Test {
+            name,
+            event,
+            payload,
+            tool,
+            set,
+        } => test(&reg, &name, &event, payload.as_deref(), tool.as_deref(), &set),
+        HookAction::New { name, description } => new_hook(&reg.primary().root, &name, description),
     }
 }
 
-fn install(root: &Path, name: &str, global: bool, target: Option<&Path>, dry_run: bool) -> Result<()> {
-    let hook_def = registry::hooks_dir(root)
-        .join(name)
-        .join("HOOK.json");
+fn install(
+    reg: &Registry,
+    name: &str,
+    settings: &Path,
+    set: &[(String, String)],
+    opts: &InstallOptions,
+) -> Result<()> {
+    let (source, name) = reg.resolve(ItemKind::Hook, name)?;
+    transaction::apply_all(&[name], false, |tx, name| {
+        installer::sync_hook_into(tx, &source.root, name, settings, set, opts)
+    })?;
 
-    if !hook_def.is_file() {
-        bail!("Hook '{}' not found in registry", name);
+    if opts.dry_run {
+        println!("\nDry run complete. No files were modified.");
     }
+    Ok(())
+}
 
-    let settings = registry::settings_path(global, target)?;
-    installer::install_hook(&hook_def, &settings, dry_run)?;
+fn uninstall(name: &str, settings: &Path) -> Result<()> {
+    installer::uninstall_hook(None, name, settings)?;
+    let claude_dir = claude_dir(settings);
+    let lock = Lockfile::load(claude_dir)?;
+    match lock.get(ItemKind::Hook, name) {
+        Some(e) if e.settings_file() == settings_file(settings) => {
+            lockfile::forget(claude_dir, ItemKind::Hook, name)
+        }
+        _ => Ok(()),
+    }
+}
 
-    if dry_run {
-        println!("\nDry run complete. No files were modified.");
+/// The settings file of a hook install or uninstall; managed settings are read-only.
+fn writable_settings(
+    scope: Option<SettingsScope>,
+    global: bool,
+    target: Option<&Path>,
+) -> Result<PathBuf> {
+    if scope == Some(SettingsScope::Managed) {
+        bail!("Managed settings are read-only; they are deployed by an administrator");
     }
-    Ok(())
+    registry::scoped_settings_path(scope, global, target)
 }
 
-fn uninstall(name: &str, global: bool, target: Option<&Path>) -> Result<()> {
-    let settings = registry::settings_path(global, target)?;
-    installer::uninstall_hook(name, &settings)
+/// The `.claude` directory containing a settings.json (where the lockfile lives).
+fn claude_dir(settings: &Path) -> &Path {
+    settings.parent().unwrap_or(Path::new("."))
 }
 
-fn list(global: bool, target: Option<&Path>) -> Result<()> {
-    let settings_path = registry::settings_path(global, target)?;
+/// `settings.json` or `settings.local.json`, as recorded in the lockfile.
+fn settings_file(settings: &Path) -> &str {
+    settings
+        .file_name()
+        .and_then(|f| f.to_str())
+        .unwrap_or(lockfile::SETTINGS_FILE)
+}
 
+fn list(settings_path: &Path) -> Result<()> {
     if !settings_path.exists() {
         println!("No hooks installed (settings.json not found at {})", settings_path.display());
         return Ok(());
     }
 
-    let content = std::fs::read_to_string(&settings_path)?;
-    let v: serde_json::Value = serde_json::from_str(&content)?;
+    let settings = installer::load_settings(settings_path)?;
 
-    let hooks_obj = match v.get("hooks").and_then(|h| h.as_object()) {
+    let hooks_obj = match settings.get("hooks").and_then(|h| h.as_object()) {
         Some(obj) => obj,
         None => {
             println!("No hooks installed in {}", settings_path.display());
@@ -68,14 +137,17 @@ fn list(global: bool, target: Option<&Path>) -> Result<()> {
         }
     };
 
-    // Collect unique _registry_id values
+    // Collect unique _registry_id values with their recorded parameters
     let mut ids: Vec<String> = Vec::new();
+    let mut params: Vec<Option<&serde_json::Map<String, Value>>> = Vec::new();
     for arr_val in hooks_obj.values() {
         if let Some(arr) = arr_val.as_array() {
             for entry in arr {
                 if let Some(id) = entry.get("_registry_id").and_then(|v| v.as_str()) {
                     if !ids.contains(&id.to_string()) {
                         ids.push(id.to_string());
+                        let recorded = entry.get(hook_params::PARAMS_KEY);
+                        params.push(recorded.and_then(|p| p.as_object()));
                     }
                 }
             }
@@ -84,35 +156,283 @@ fn list(global: bool, target: Option<&Path>) -> Result<()> {
 
     if ids.is_empty() {
         println!("No registry-managed hooks in {}", settings_path.display());
-    } else {
-        println!("Installed hooks in {}:", settings_path.display());
-        for id in &ids {
-            println!("  {}", id);
+        return Ok(());
+    }
+
+    let lock = Lockfile::load(claude_dir(settings_path))?;
+    println!("Installed hooks in {}:", settings_path.display());
+    for (id, params) in ids.iter().zip(params) {
+        match lock.get(ItemKind::Hook, id) {
+            Some(e) if e.settings_file() == settings_file(settings_path) => {
+                println!("  {:<24} registry: {} ({})", id, e.source, e.installed_at)
+            }
+            _ => println!("  {:<24} not recorded in registry-lock.json", id),
+        }
+        if let Some(params) = params.filter(|p| !p.is_empty()) {
+            println!("  {:<24} params: {}", "", hook_params::format_values(params));
         }
     }
     Ok(())
 }
 
-fn available(root: &Path) -> Result<()> {
-    let hook_dirs = registry::list_hooks(root)?;
+/// Every scope's settings file as Claude Code sees it from `target` (or the current directory).
+fn list_effective(target: Option<&Path>) -> Result<()> {
+    let project = match target {
+        Some(t) => t.to_path_buf(),
+        None => std::env::current_dir()?,
+    };
+    let mut files = Vec::new();
+    for scope in SettingsScope::PRECEDENCE {
+        let target = match scope {
+            SettingsScope::Project | SettingsScope::Local => Some(project.as_path()),
+            SettingsScope::Managed | SettingsScope::User => None,
+        };
+        files.push((scope, registry::scope_settings_path(scope, target)?));
+    }
+    println!("Effective hooks for {} (highest precedence first):", project.display());
+    list_scopes(&files)
+}
+
+/// List the hook groups of each settings file, grouped by event.
+///
+/// Hooks are not overridden between scopes: Claude Code runs the matching
+/// hooks of all of them, so every group is shown.
+fn list_scopes(files: &[(SettingsScope, PathBuf)]) -> Result<()> {
+    let mut layers = Vec::new();
+    for (scope, path) in files {
+        let settings = match path.exists() {
+            true => Some(installer::load_settings(path)?),
+            false => None,
+        };
+        let state = if settings.is_none() { " (not found)" } else { "" };
+        println!("  {:<8} {}{}", scope, path.display(), state);
+        layers.push((*scope, settings.unwrap_or_default()));
+    }
+
+    // Scalar settings follow precedence: the highest scope that sets one wins.
+    let setting = |key: &str| {
+        layers
+            .iter()
+            .find_map(|(scope, s)| Some((*scope, s.get(key)?.as_bool()?)))
+    };
+    if let Some((scope, true)) = setting("disableAllHooks") {
+        println!("\n  Note: disableAllHooks is set in {} settings", scope);
+    }
+    let managed_only = layers.iter().any(|(scope, s)| {
+        *scope == SettingsScope::Managed && s.get("allowManagedHooksOnly") == Some(&Value::Bool(true))
+    });
+    if managed_only {
+        println!("\n  Note: allowManagedHooksOnly is set; only managed hooks run");
+    }
+
+    let mut events: Vec<&str> = lint::HOOK_EVENTS.to_vec();
+    for (_, settings) in &layers {
+        let hooks = settings.get("hooks").and_then(Value::as_object);
+        for event in hooks.into_iter().flat_map(|h| h.keys()) {
+            if !events.contains(&event.as_str()) {
+                events.push(event);
+            }
+        }
+    }
+    let mut any = false;
+    for event in events {
+        let mut lines = Vec::new();
+        for (scope, settings) in &layers {
+            let groups = settings.get("hooks").and_then(|h| h.get(event)).and_then(Value::as_array);
+            for group in groups.into_iter().flatten() {
+                let owner = group.get("_registry_id").and_then(Value::as_str).unwrap_or("-");
+                let matcher = match group.get("matcher").and_then(Value::as_str).unwrap_or("") {
+                    "" => String::new(),
+                    m => format!("[{}] ", m),
+                };
+                let ignored = if managed_only && *scope != SettingsScope::Managed {
+                    " (ignored)"
+                } else {
+                    ""
+                };
+                for hook in group.get("hooks").and_then(Value::as_array).into_iter().flatten() {
+                    let run = match hook.get("type").and_then(Value::as_str) {
+                        Some("command") => {
+                            hook.get("command").and_then(Value::as_str).unwrap_or("")
+                        }
+                        Some(kind) => kind,
+                        None => "",
+                    };
+                    let line = format!("    {:<8} {:<24} {}{}", scope, owner, matcher, run);
+                    lines.push(format!("{}{}", line, ignored));
+                }
+            }
+        }
+        if !lines.is_empty() {
+            any = true;
+            println!("\n  {}:", event);
+            lines.iter().for_each(|l| println!("{}", l));
+        }
+    }
+    if !any {
+        println!("\n  No hooks in any scope");
+    }
+    Ok(())
+}
+
+fn test(
+    reg: &Registry,
+    name: &str,
+    event: &str,
+    payload: Option<&Path>,
+    tool: Option<&str>,
+    set: &[(String, String)],
+) -> Result<()> {
+    if !lint::HOOK_EVENTS.contains(&event) {
+        bail!("Unknown hook event '{}' (expected one of: {})", event, lint::HOOK_EVENTS.join(", "));
+    }
+    let (source, name) = reg.resolve(ItemKind::Hook, name)?;
+    let hook_dir = registry::hooks_dir(&source.root).join(&name);
+    let hook_def = hook_dir.join("HOOK.json");
+    if !hook_def.is_file() {
+        bail!("Hook '{}' not found in registry", name);
+    }
+    let content = std::fs::read_to_string(&hook_def)?;
+    let def: Value = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", hook_def.display()))?;
+    let params: hook_params::Parameters = match def.get("parameters") {
+        Some(p) => serde_json::from_value(p.clone())
+            .with_context(|| format!("Invalid parameters in {}", hook_def.display()))?,
+        None => hook_params::Parameters::new(),
+    };
+    let overrides = hook_params::merge_overrides(&params, None, set)?;
+    let values = hook_params::resolve(&params, &overrides)?;
+    let Some(groups) = def.get("hooks").and_then(|h| h.get(event)).and_then(Value::as_array) else {
+        let events: Vec<&str> = def
+            .get("hooks")
+            .and_then(Value::as_object)
+            .map(|h| h.keys().map(String::as_str).collect())
+            .unwrap_or_default();
+        bail!("Hook '{}' has no {} hooks (it defines: {})", name, event, events.join(", "));
+    };
+
+    let cwd = std::env::current_dir()?;
+    let mut input = hook_runner::default_payload(event, &cwd);
+    if let Some(path) = payload {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let Value::Object(fields) = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?
+        else {
+            bail!("{} must contain a JSON object", path.display());
+        };
+        if let Some(input) = input.as_object_mut() {
+            input.extend(fields);
+        }
+    }
+    if let Some(tool) = tool {
+        input["tool_name"] = Value::from(tool);
+    }
+    input["hook_event_name"] = Value::from(event);
+
+    let field = hook_runner::matcher_field(event);
+    let subject = field.and_then(|f| input.get(f)).and_then(Value::as_str).unwrap_or("");
+    match field {
+        Some(field) => println!("Testing hook '{}' on {} ({}: {}):", name, event, field, subject),
+        None => println!("Testing hook '{}' on {}:", name, event),
+    }
+    let stdin = serde_json::to_string(&input)?;
+    println!("  payload: {}", stdin);
+
+    let (mut ran, mut blocked, mut failed) = (0, 0, 0);
+    for (i, group) in groups.iter().enumerate() {
+        let mut group = group.clone();
+        installer::expand_hook_dir(&mut group, &hook_dir);
+        hook_params::substitute(&mut group, &values);
+        let matcher = group.get("matcher").and_then(Value::as_str).unwrap_or("");
+        if field.is_some() && !hook_runner::matches(matcher, subject)? {
+            println!("  group {} (matcher '{}'): no match", i, matcher);
+            continue;
+        }
+        println!("  group {} (matcher '{}'):", i, matcher);
+        for hook in group.get("hooks").and_then(Value::as_array).into_iter().flatten() {
+            let kind = hook.get("type").and_then(Value::as_str).unwrap_or("");
+            if kind != "command" {
+                println!("    {} hook skipped (evaluated by Claude, not run locally)", kind);
+                continue;
+            }
+            let command = hook.get("command").and_then(Value::as_str).unwrap_or("");
+            let timeout = match hook.get("timeout").and_then(Value::as_f64) {
+                None => hook_runner::DEFAULT_TIMEOUT,
+                Some(secs) => Duration::try_from_secs_f64(secs)
+                    .ok()
+                    .filter(|t| !t.is_zero())
+                    .with_context(|| {
+                        format!("invalid timeout {} (must be a positive number of seconds)", secs)
+                    })?,
+            };
+            println!("    $ {}", command);
+            let out = hook_runner::run_command(command, &stdin, &cwd, timeout)?;
+            ran += 1;
+            let ms = out.elapsed.as_millis();
+            match out.code {
+                _ if out.timed_out => {
+                    failed += 1;
+                    println!("      timed out after {}s", timeout.as_secs_f64());
+                }
+                Some(0) => println!("      exit 0 ({} ms)", ms),
+                Some(2) => {
+                    blocked += 1;
+                    println!("      exit 2 ({} ms): blocking, stderr is fed back to Claude", ms);
+                }
+                Some(code) => {
+                    failed += 1;
+                    println!("      exit {} ({} ms): non-blocking error", code, ms);
+                }
+                None => {
+                    failed += 1;
+                    println!("      killed by a signal ({} ms)", ms);
+                }
+            }
+            for (label, text) in [("stdout", &out.stdout), ("stderr", &out.stderr)] {
+                for line in text.lines() {
+                    println!("      {}| {}", label, line);
+                }
+            }
+            if let Some(decision) = out.decision() {
+                println!("      decision: {}", Value::Object(decision));
+            }
+        }
+    }
+
+    if ran == 0 {
+        println!("  No commands matched.");
+        return Ok(());
+    }
+    println!("\n{} command(s) ran: {} blocking, {} failed", ran, blocked, failed);
+    if failed > 0 {
+        bail!("{} hook command(s) failed or timed out", failed);
+    }
+    Ok(())
+}
+
+fn available(reg: &Registry) -> Result<()> {
+    let hooks = reg.list(registry::list_hooks, registry::dir_name)?;
 
     println!("Available Hooks:");
     println!();
-    for hook_dir in &hook_dirs {
-        let name = hook_dir
-            .file_name()
-            .unwrap_or_default()
-            .to_string_lossy();
-        let hook_json = hook_dir.join("HOOK.json");
-        let desc = if hook_json.is_file() {
+    for hook in &hooks {
+        let hook_json = hook.path.join("HOOK.json");
+        let (desc, params) = if hook_json.is_file() {
             let content = std::fs::read_to_string(&hook_json)?;
             let v: serde_json::Value = serde_json::from_str(&content)?;
-            v.get("description")
+            let desc = v
+                .get("description")
                 .and_then(|d| d.as_str())
                 .unwrap_or("")
-                .to_string()
+                .to_string();
+            let params: hook_params::Parameters = v
+                .get("parameters")
+                .and_then(|p| serde_json::from_value(p.clone()).ok())
+                .unwrap_or_default();
+            (desc, params)
         } else {
-            String::new()
+            (String::new(), hook_params::Parameters::new())
         };
         // Truncate long descriptions (char-boundary safe)
         let desc_short: String = if desc.chars().count() > 60 {
@@ -122,10 +442,17 @@ fn available(root: &Path) -> Result<()> {
         } else {
             desc
         };
-        println!("  {:<24} {}", name, desc_short);
+        println!("{}", hook.row(reg, &desc_short));
+        for (name, param) in &params {
+            let default = match &param.default {
+                Some(v) => format!(" = {}", hook_params::display(v)),
+                None => String::new(),
+            };
+            println!("      --set {}=<{}>{}  {}", name, param.kind, default, param.description);
+        }
     }
 
-    if hook_dirs.is_empty() {
+    if hooks.is_empty() {
         println!("  (none)");
     }
     Ok(())
@@ -0,0 +1,20 @@
+use anyhow::{bail, Result};
+
+use crate::lint;
+use crate::registry::Registry;
+
+pub fn run() -> Result<()> {
+    let reg = Registry::load()?;
+    let root = &reg.primary().root;
+
+    println!("Linting {}:", root.display());
+    let found = lint::lint(root, Some(&reg))?;
+    for diagnostic in &found {
+        println!("  {}", diagnostic);
+    }
+    if !found.is_empty() {
+        bail!("{} problem(s) found", found.len());
+    }
+    println!("  No problems found.");
+    Ok(())
+}
@@ -0,0 +1,144 @@
+use anyhow::Result;
+use serde_json::Value;
+use std::io::IsTerminal;
+use std::path::Path;
+
+use crate::cli::McpAction;
+use crate::installer::{self, InstallOptions};
+use crate::lockfile::{self, ItemKind, Lockfile};
+use crate::mcp;
+use crate::registry::{self, Registry};
+use crate::transaction;
+
+pub fn run(action: McpAction) -> Result<()> {
+    let reg = Registry::load()?;
+
+    match action {
+        McpAction::Install {
+            name,
+            global,
+            target,
+            dry_run,
+            allow_unsigned,
+            accept_risk,
+            env,
+        } => {
+            let opts = InstallOptions {
+                dry_run,
+                allow_unsigned,
+                accept_risk,
+                ..Default::default()
+            };
+            let claude_dir = registry::claude_dir(global, target.as_deref())?;
+            install(&reg, &name, &claude_dir, &env, &opts)
+        }
+        McpAction::Uninstall {
+            name,
+            global,
+            target,
+        } => uninstall(&name, &registry::claude_dir(global, target.as_deref())?),
+        McpAction::List { global, target } => {
+            list(&registry::claude_dir(global, target.as_deref())?)
+        }
+        McpAction::Available => available(&reg),
+    }
+}
+
+fn install(
+    reg: &Registry,
+    name: &str,
+    claude_dir: &Path,
+    env: &[(String, String)],
+    opts: &InstallOptions,
+) -> Result<()> {
+    let (source, name) = reg.resolve(ItemKind::Mcp, name)?;
+
+    // Variables not given with --env are asked for when a user is at the terminal.
+    let prompt = !opts.dry_run && std::io::stdin().is_terminal();
+    transaction::apply_all(&[name], false, |tx, name| {
+        installer::sync_mcp_with(tx, &source.root, name, claude_dir, env, prompt, opts)
+    })?;
+
+    if opts.dry_run {
+        println!("\nDry run complete. No files were modified.");
+    }
+    Ok(())
+}
+
+fn uninstall(name: &str, claude_dir: &Path) -> Result<()> {
+    installer::uninstall_mcp(None, name, &registry::mcp_config_path(claude_dir))?;
+    lockfile::forget(claude_dir, ItemKind::Mcp, name)
+}
+
+fn list(claude_dir: &Path) -> Result<()> {
+    let config_path = registry::mcp_config_path(claude_dir);
+    if !config_path.exists() {
+        println!("No MCP servers ({} not found)", config_path.display());
+        return Ok(());
+    }
+    let config = installer::load_settings(&config_path)?;
+    let servers = config.get("mcpServers").and_then(Value::as_object);
+    let Some(servers) = servers.filter(|s| !s.is_empty()) else {
+        println!("No MCP servers in {}", config_path.display());
+        return Ok(());
+    };
+
+    let lock = Lockfile::load(claude_dir)?;
+    println!("MCP servers in {}:", config_path.display());
+    for (server, entry) in servers {
+        let owner = entry.get(mcp::OWNER_KEY).and_then(Value::as_str);
+        let state = match owner {
+            Some(owner) if lock.get(ItemKind::Mcp, owner).is_none() => {
+                " (not recorded in registry-lock.json)"
+            }
+            _ => "",
+        };
+        println!(
+            "  {:<24} {:<24} {}{}",
+            server,
+            owner.unwrap_or("-"),
+            mcp::describe(entry),
+            state
+        );
+    }
+    println!("\n'-' marks servers not added by a registry item.");
+    Ok(())
+}
+
+fn available(reg: &Registry) -> Result<()> {
+    let items = reg.list(registry::list_mcp, registry::dir_name)?;
+
+    println!("Available MCP Servers:");
+    println!();
+    for item in &items {
+        let def = mcp::load(&item.path.join("MCP.json"))?;
+        // Truncate long descriptions (char-boundary safe)
+        let desc: String = if def.description.chars().count() > 60 {
+            let mut s: String = def.description.chars().take(57).collect();
+            s.push_str("...");
+            s
+        } else {
+            def.description.clone()
+        };
+        println!("{}", item.row(reg, &desc));
+        for (server, entry) in &def.servers {
+            println!("      {:<20} {}", server, mcp::describe(entry));
+        }
+        for (name, var) in &def.variables {
+            let default = match (&var.default, var.secret) {
+                (_, true) => " (secret)".to_string(),
+                (Some(d), false) => format!(" = {}", d),
+                (None, false) => String::new(),
+            };
+            println!(
+                "      --env {}=<value>{}  {}",
+                name, default, var.description
+            );
+        }
+    }
+
+    if items.is_empty() {
+        println!("  (none)");
+    }
+    Ok(())
+}
@@ -1,6 +1,16 @@
 pub mod agent;
+pub mod apply;
+pub mod audit;
 pub mod catalog;
 pub mod complete;
 pub mod hook;
+pub mod lint;
+pub mod mcp;
+pub mod permissions;
+pub mod plan;
 pub mod profile;
+pub mod settings;
+pub mod sign;
 pub mod skill;
+pub mod source;
+pub mod update;
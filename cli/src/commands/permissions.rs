@@ -0,0 +1,65 @@
+use anyhow::Result;
+use serde_json::Value;
+use std::path::Path;
+
+use crate::cli::PermissionsAction;
+use crate::installer;
+use crate::permissions;
+use crate::registry;
+
+pub fn run(action: PermissionsAction) -> Result<()> {
+    match action {
+        PermissionsAction::List {
+            global,
+            target,
+            scope,
+        } => list(&registry::scoped_settings_path(
+            scope,
+            global,
+            target.as_deref(),
+        )?),
+    }
+}
+
+fn list(settings_path: &Path) -> Result<()> {
+    if !settings_path.exists() {
+        println!(
+            "No permissions (settings file not found at {})",
+            settings_path.display()
+        );
+        return Ok(());
+    }
+    let settings = installer::load_settings(settings_path)?;
+    let owners = permissions::owners(&settings);
+    let rules = settings.get("permissions");
+
+    let mut rows = Vec::new();
+    // `ask` rules cannot be declared by items, but are listed for the full picture.
+    for list in ["allow", "ask", "deny"] {
+        let items = rules.and_then(|r| r.get(list)).and_then(Value::as_array);
+        for rule in items.into_iter().flatten().filter_map(Value::as_str) {
+            let by: Vec<&str> = owners
+                .iter()
+                .filter(|(_, r)| r.list(list).iter().any(|r| r == rule))
+                .map(|(owner, _)| owner.as_str())
+                .collect();
+            let by = if by.is_empty() {
+                "-".to_string()
+            } else {
+                by.join(", ")
+            };
+            rows.push((list, rule, by));
+        }
+    }
+
+    if rows.is_empty() {
+        println!("No permission rules in {}", settings_path.display());
+        return Ok(());
+    }
+    println!("Permissions in {}:", settings_path.display());
+    for (list, rule, by) in rows {
+        println!("  {:<6} {:<40} {}", list, rule, by);
+    }
+    println!("\n'-' marks rules not added by a registry item.");
+    Ok(())
+}
@@ -0,0 +1,23 @@
+use anyhow::Result;
+use std::path::Path;
+
+use crate::manifest::{self, Manifest};
+use crate::registry::Registry;
+
+pub fn run(claude_dir: &Path) -> Result<()> {
+    let reg = Registry::load()?;
+    let manifest = Manifest::load(claude_dir)?;
+    let changes = manifest::plan(&reg, claude_dir, &manifest)?;
+
+    println!(
+        "Plan for {} ({}):",
+        claude_dir.display(),
+        manifest::MANIFEST_FILE
+    );
+    if manifest::print_plan(&changes) == 0 {
+        println!("Nothing to do.");
+    } else {
+        println!("Run `claude-registry apply` to converge.");
+    }
+    Ok(())
+}
@@ -1,85 +1,73 @@
-use anyhow::{bail, Result};
-use serde::Deserialize;
+use anyhow::Result;
 use std::path::Path;
 
 use crate::cli::ProfileAction;
-use crate::installer;
-use crate::registry;
-
-#[derive(Deserialize)]
-struct Profile {
-    name: String,
-    #[serde(default)]
-    description: String,
-    #[serde(default)]
-    skills: Vec<String>,
-    #[serde(default)]
-    agents: Vec<String>,
-}
+use crate::installer::{self, InstallOptions};
+use crate::lockfile::ItemKind;
+use crate::registry::{self, Registry};
+use crate::transaction;
 
 pub fn run(action: ProfileAction) -> Result<()> {
-    let root = registry::resolve_root()?;
+    let reg = Registry::load()?;
 
     match action {
         ProfileAction::Install {
             name,
+            global,
             target,
             dry_run,
-        } => install(&root, &name, &target, dry_run),
-        ProfileAction::List => list(&root),
+            strategy,
+            keep_going,
+            allow_unsigned,
+            accept_risk,
+        } => {
+            let opts = InstallOptions {
+                strategy,
+                dry_run,
+                link: false,
+                allow_unsigned,
+                accept_risk,
+            };
+            let claude_dir = registry::claude_dir(global, target.as_deref())?;
+            install(&reg, &name, &claude_dir, &opts, keep_going)
+        }
+        ProfileAction::List => list(&reg),
     }
 }
 
-fn install(root: &Path, name: &str, target: &Path, dry_run: bool) -> Result<()> {
-    let profile_path = registry::profiles_dir(root).join(format!("{}.json", name));
-    if !profile_path.is_file() {
-        bail!("Profile '{}' not found", name);
-    }
-
-    let content = std::fs::read_to_string(&profile_path)?;
-    let profile: Profile = serde_json::from_str(&content)?;
+fn install(
+    reg: &Registry,
+    name: &str,
+    claude_dir: &Path,
+    opts: &InstallOptions,
+    keep_going: bool,
+) -> Result<()> {
+    let (source, profile) = reg.resolve_profile(name)?;
 
     let total = profile.skills.len() + profile.agents.len();
     println!(
         "Installing profile '{}' ({} item(s)) to {}",
         profile.name,
         total,
-        target.display()
+        claude_dir.display()
     );
 
-    // Install skills
-    if !profile.skills.is_empty() {
-        let skills_dir = registry::skills_dir(root);
-        let target_skills = target.join(".claude").join("skills");
-
-        for skill_name in &profile.skills {
-            let src = skills_dir.join(skill_name);
-            let dst = target_skills.join(skill_name);
-            if let Err(e) = installer::install_skill(&src, &dst, dry_run) {
-                eprintln!("  Warning: {}", e);
-            }
+    // Skills and agents are applied as one transaction
+    let items: Vec<(ItemKind, &String)> = profile
+        .skills
+        .iter()
+        .map(|s| (ItemKind::Skill, s))
+        .chain(profile.agents.iter().map(|a| (ItemKind::Agent, a)))
+        .collect();
+    transaction::apply_all(&items, keep_going, |tx, (kind, item)| {
+        let (source, item) = reg.resolve_member(source, *kind, item)?;
+        match kind {
+            ItemKind::Agent => installer::sync_agent(tx, &source.root, &item, claude_dir, opts),
+            _ => installer::sync_skill(tx, &source.root, &item, claude_dir, opts),
         }
-    }
-
-    // Install agents
-    if !profile.agents.is_empty() {
-        let agents_dir = registry::agents_dir(root);
-        let target_agents = target.join(".claude").join("agents");
-
-        if !dry_run {
-            std::fs::create_dir_all(&target_agents)?;
-        }
-
-        for agent_name in &profile.agents {
-            let src = agents_dir.join(format!("{}.md", agent_name));
-            let dst = target_agents.join(format!("{}.md", agent_name));
-            if let Err(e) = installer::install_agent(&src, &dst, dry_run) {
-                eprintln!("  Warning: {}", e);
-            }
-        }
-    }
+    })?;
 
-    if dry_run {
+    if opts.dry_run {
         println!("\nDry run complete. No files were copied.");
     } else {
         println!("\nDone!");
@@ -87,19 +75,14 @@ fn install(root: &Path, name: &str, target: &Path, dry_run: bool) -> Result<()>
     Ok(())
 }
 
-fn list(root: &Path) -> Result<()> {
-    let profiles = registry::list_profiles(root)?;
+fn list(reg: &Registry) -> Result<()> {
+    let profiles = reg.list(registry::list_profiles, registry::file_stem)?;
 
     println!("Available Profiles:");
     println!();
-    for profile_path in &profiles {
-        let content = std::fs::read_to_string(profile_path)?;
-        let profile: Profile = serde_json::from_str(&content)?;
-        let name = profile_path
-            .file_stem()
-            .unwrap_or_default()
-            .to_string_lossy();
-        println!("  {:<24} {}", name, profile.description);
+    for listed in &profiles {
+        let profile = registry::read_profile(&listed.path)?;
+        println!("{}", listed.row(reg, &profile.description));
 
         if !profile.skills.is_empty() {
             println!("    skills: {}", profile.skills.join(", "));
@@ -0,0 +1,138 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+use crate::cli::SettingsAction;
+use crate::installer;
+use crate::lockfile::{self, ItemKind, Lockfile};
+use crate::registry::{self, SettingsScope};
+use crate::settings_history;
+
+pub fn run(action: SettingsAction) -> Result<()> {
+    match action {
+        SettingsAction::History {
+            global,
+            target,
+            scope,
+        } => history(global, target.as_deref(), scope),
+        SettingsAction::Restore { id, dry_run } => restore(&id, dry_run),
+    }
+}
+
+fn history(global: bool, target: Option<&Path>, scope: Option<SettingsScope>) -> Result<()> {
+    let path = match global || target.is_some() || scope.is_some() {
+        true => Some(registry::scoped_settings_path(scope, global, target)?),
+        false => None,
+    };
+    let snapshots = settings_history::list(path.as_deref())?;
+    let dir = settings_history::history_dir()?;
+    if snapshots.is_empty() {
+        println!("No settings snapshots in {}", dir.display());
+        return Ok(());
+    }
+
+    println!("Settings snapshots in {} (newest first):", dir.display());
+    for s in &snapshots {
+        let state = if s.content.is_none() {
+            " (did not exist)"
+        } else {
+            ""
+        };
+        println!(
+            "  {:<20} before {:<32} {}{}",
+            s.id,
+            s.action,
+            s.path.display(),
+            state
+        );
+    }
+    println!("\nRestore one with `claude-registry settings restore <id>`.");
+    Ok(())
+}
+
+fn restore(id: &str, dry_run: bool) -> Result<()> {
+    let snapshot = settings_history::load(id)?;
+    let path = &snapshot.path;
+    let current = match path.exists() {
+        true => Some(
+            fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?,
+        ),
+        false => None,
+    };
+    let wanted = snapshot.content.as_deref();
+
+    if dry_run {
+        println!(
+            "[dry-run] Would restore {} to snapshot {} (taken before '{}'):",
+            path.display(),
+            id,
+            snapshot.action
+        );
+        installer::print_diff(path, current.as_deref().unwrap_or(""), wanted.unwrap_or(""));
+        return Ok(());
+    }
+    if current.as_deref() == wanted {
+        println!("{} already matches snapshot {}", path.display(), id);
+        return Ok(());
+    }
+
+    let backup = settings_history::record(path, &format!("settings restore {}", id))?;
+    match wanted {
+        Some(content) => {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create {}", parent.display()))?;
+            }
+            fs::write(path, content)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+        None => {
+            fs::remove_file(path).with_context(|| format!("Failed to remove {}", path.display()))?
+        }
+    }
+    println!(
+        "Restored {} to snapshot {} (taken before '{}')",
+        path.display(),
+        id,
+        snapshot.action
+    );
+    println!("  Previous settings saved as snapshot {}", backup.id);
+
+    // Hooks and MCP servers the restored file no longer contains are no longer installed.
+    let parent = path.parent().unwrap_or(Path::new("."));
+    let file = path.file_name().and_then(|f| f.to_str()).unwrap_or_default();
+    if matches!(file, ".mcp.json" | ".claude.json") {
+        return forget_mcp(&parent.join(".claude"), path);
+    }
+    let claude_dir = parent;
+    let lock = Lockfile::load(claude_dir)?;
+    for entry in lock.entries(ItemKind::Hook) {
+        if entry.settings_file() == file && !installer::hook_installed(&entry.name, path)? {
+            lockfile::forget(claude_dir, ItemKind::Hook, &entry.name)?;
+            println!(
+                "  Forgot hook '{}' in registry-lock.json (not in the restored settings)",
+                entry.name
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Forget the MCP servers recorded in `claude_dir` that a restored MCP config lacks.
+fn forget_mcp(claude_dir: &Path, config: &Path) -> Result<()> {
+    if registry::mcp_config_path(claude_dir) != config {
+        return Ok(());
+    }
+    let lock = Lockfile::load(claude_dir)?;
+    for entry in lock.entries(ItemKind::Mcp) {
+        if !installer::mcp_installed(&entry.name, config)? {
+            lockfile::forget(claude_dir, ItemKind::Mcp, &entry.name)?;
+            println!(
+                "  Forgot MCP server '{}' in registry-lock.json (not in the restored file)",
+                entry.name
+            );
+        }
+    }
+    Ok(())
+}
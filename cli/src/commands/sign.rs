@@ -0,0 +1,55 @@
+use anyhow::{bail, Result};
+use std::path::PathBuf;
+
+use crate::catalog;
+use crate::config;
+use crate::lockfile::ItemKind;
+use crate::registry;
+use crate::signing;
+
+pub fn run(files: Vec<PathBuf>, key: Option<PathBuf>, generate_key: bool) -> Result<()> {
+    let key_path = match key {
+        Some(path) => path,
+        None => signing::default_key_path()?,
+    };
+    if generate_key {
+        let public = signing::generate_key(&key_path)?;
+        println!("Created signing key {}", key_path.display());
+        println!("  Public key: {}", signing::public_key_hex(&public));
+        println!(
+            "\nAdd the public key to trusted_keys in {} to accept content it signs.",
+            config::config_path()?.display()
+        );
+        return Ok(());
+    }
+
+    let key = signing::load_key(&key_path)?;
+    let files = if files.is_empty() {
+        let root = registry::resolve_root()?;
+        let kinds = [ItemKind::Skill, ItemKind::Agent, ItemKind::Hook, ItemKind::Mcp];
+        let catalogs: Vec<PathBuf> = kinds
+            .into_iter()
+            .map(|kind| root.join(catalog::file_name(kind)))
+            .filter(|path| path.is_file())
+            .collect();
+        if catalogs.is_empty() {
+            bail!(
+                "No catalogs found in {}; run `claude-registry catalog build` first",
+                root.display()
+            );
+        }
+        catalogs
+    } else {
+        files
+    };
+
+    println!(
+        "Signing with key {}:",
+        signing::public_key_hex(&key.verifying_key())
+    );
+    for file in &files {
+        let sig = signing::sign_file(&key, file)?;
+        println!("  {} -> {}", file.display(), sig.display());
+    }
+    Ok(())
+}
@@ -1,30 +1,68 @@
 use anyhow::{bail, Result};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
+use crate::archive;
+use crate::catalog::{self, CatalogItem};
 use crate::cli::SkillAction;
-use crate::frontmatter::parse_frontmatter;
-use crate::installer;
-use crate::registry;
+use crate::frontmatter::{self, SkillMeta};
+use crate::installer::{self, InstallOptions};
+use crate::lockfile::{self, ItemKind, Lockfile};
+use crate::registry::{self, Registry};
+use crate::transaction;
 
 pub fn run(action: SkillAction) -> Result<()> {
-    let root = registry::resolve_root()?;
+    let reg = Registry::load()?;
 
     match action {
         SkillAction::Install {
             names,
+            global,
             target,
             dry_run,
-        } => install(&root, &names, &target, dry_run),
-        SkillAction::List { target } => list(&target),
-        SkillAction::New { name, description } => new_skill(&root, &name, description),
-        SkillAction::Available => available(&root),
-        SkillAction::Uninstall { name, target } => uninstall(&name, &target),
+            strategy,
+            keep_going,
+            link,
+            catalog,
+            from_archive,
+            allow_unsigned,
+            accept_risk,
+        } => {
+            let opts = InstallOptions {
+                strategy,
+                dry_run,
+                link,
+                allow_unsigned,
+                accept_risk,
+            };
+            let claude_dir = registry::claude_dir(global, target.as_deref())?;
+            match from_archive {
+                Some(archive) => install_archive(&archive, &claude_dir, &opts),
+                None => install(&reg, &names, catalog.as_deref(), &claude_dir, &opts, keep_going),
+            }
+        }
+        SkillAction::Pack { name, output } => pack(&reg, &name, output),
+        SkillAction::List { global, target } => {
+            list(&registry::claude_dir(global, target.as_deref())?)
+        }
+        SkillAction::New { name, description } => new_skill(&reg.primary().root, &name, description),
+        SkillAction::Available => available(&reg),
+        SkillAction::Uninstall {
+            name,
+            global,
+            target,
+        } => uninstall(&name, &registry::claude_dir(global, target.as_deref())?),
     }
 }
 
-fn install(root: &Path, names: &[String], target: &Path, dry_run: bool) -> Result<()> {
-    let skills_dir = registry::skills_dir(root);
-    let target_skills = target.join(".claude").join("skills");
+fn install(
+    reg: &Registry,
+    names: &[String],
+    catalog: Option<&Path>,
+    claude_dir: &Path,
+    opts: &InstallOptions,
+    keep_going: bool,
+) -> Result<()> {
+    let target_skills = claude_dir.join("skills");
 
     println!(
         "Installing {} skill(s) to {}",
@@ -32,15 +70,16 @@ fn install(root: &Path, names: &[String], target: &Path, dry_run: bool) -> Resul
         target_skills.display()
     );
 
-    for name in names {
-        let src = skills_dir.join(name);
-        let dst = target_skills.join(name);
-        if let Err(e) = installer::install_skill(&src, &dst, dry_run) {
-            eprintln!("  Warning: {}", e);
+    transaction::apply_all(names, keep_going, |tx, name| {
+        if let Some(catalog) = catalog {
+            let item = catalog::find_skill(catalog, name)?;
+            return installer::sync_catalog_skill(tx, &item, claude_dir, opts);
         }
-    }
+        let (source, name) = reg.resolve(ItemKind::Skill, name)?;
+        installer::sync_skill(tx, &source.root, &name, claude_dir, opts)
+    })?;
 
-    if dry_run {
+    if opts.dry_run {
         println!("\nDry run complete. No files were copied.");
     } else {
         println!("\nDone! Skills installed to {}", target_skills.display());
@@ -48,20 +87,86 @@ fn install(root: &Path, names: &[String], target: &Path, dry_run: bool) -> Resul
     Ok(())
 }
 
-fn list(target: &Path) -> Result<()> {
-    let target_skills = target.join(".claude").join("skills");
-    if !target_skills.is_dir() {
+/// Unpack `archive` into a scratch registry root and install from there,
+/// recording the archive itself as the skill's source.
+fn install_archive(archive: &Path, claude_dir: &Path, opts: &InstallOptions) -> Result<()> {
+    let scratch = std::env::temp_dir().join(format!("claude-registry-unpack-{}", std::process::id()));
+    transaction::remove_path(&scratch)?;
+    let result = archive::unpack_skill(archive, &scratch).and_then(|manifest| {
+        println!(
+            "Installing skill '{}' from {} to {}",
+            manifest.name,
+            archive.display(),
+            claude_dir.join("skills").display()
+        );
+        let item = CatalogItem {
+            root: scratch.clone(),
+            source: std::path::absolute(archive)?,
+            signed: archive.to_path_buf(),
+            name: manifest.name,
+            files: manifest.files,
+        };
+        transaction::apply_all(&[item], false, |tx, item| {
+            installer::sync_catalog_skill(tx, item, claude_dir, opts)
+        })
+    });
+    transaction::remove_path(&scratch)?;
+    result?;
+
+    if opts.dry_run {
+        println!("\nDry run complete. No files were copied.");
+    } else {
+        println!("\nDone! Skill installed to {}", claude_dir.join("skills").display());
+    }
+    Ok(())
+}
+
+fn pack(reg: &Registry, name: &str, output: Option<PathBuf>) -> Result<()> {
+    let (source, name) = reg.resolve(ItemKind::Skill, name)?;
+    let src = registry::skills_dir(&source.root).join(&name);
+    let out = output.unwrap_or_else(|| PathBuf::from(format!("{}.tar.gz", name)));
+    let manifest = archive::pack_skill(&src, &name, &out)?;
+    println!(
+        "Packed skill '{}' ({} file(s)) into {}",
+        name,
+        manifest.files.len(),
+        out.display()
+    );
+    Ok(())
+}
+
+fn list(claude_dir: &Path) -> Result<()> {
+    let target_skills = claude_dir.join("skills");
+    let lock = Lockfile::load(claude_dir)?;
+    if !target_skills.is_dir() && lock.entries(ItemKind::Skill).next().is_none() {
         println!("No skills installed at {}", target_skills.display());
         return Ok(());
     }
 
-    println!("Installed skills in {}:", target_skills.display());
-    for entry in std::fs::read_dir(&target_skills)? {
-        let entry = entry?;
-        if entry.path().is_dir() {
-            println!("  {}", entry.file_name().to_string_lossy());
+    let mut names = Vec::new();
+    if target_skills.is_dir() {
+        for entry in std::fs::read_dir(&target_skills)? {
+            let entry = entry?;
+            let path = entry.path();
+            let hidden = entry.file_name().to_string_lossy().starts_with('.');
+            if !hidden && (path.is_dir() || transaction::is_symlink(&path)) {
+                names.push(entry.file_name().to_string_lossy().to_string());
+            }
+        }
+    }
+    for entry in lock.entries(ItemKind::Skill) {
+        if !names.contains(&entry.name) {
+            names.push(entry.name.clone());
         }
     }
+    names.sort();
+
+    println!("Installed skills in {}:", target_skills.display());
+    for name in &names {
+        let status =
+            lockfile::describe_installed(lock.get(ItemKind::Skill, name), &target_skills.join(name));
+        println!("  {:<24} {}", name, status);
+    }
     Ok(())
 }
 
@@ -113,34 +218,32 @@ fn new_skill(root: &Path, name: &str, description: Option<String>) -> Result<()>
     Ok(())
 }
 
-fn available(root: &Path) -> Result<()> {
-    let skill_dirs = registry::list_skills(root)?;
+fn available(reg: &Registry) -> Result<()> {
+    let skills = reg.list(registry::list_skills, registry::dir_name)?;
 
     println!("Available Skills:");
     println!();
-    for skill_dir in &skill_dirs {
-        let name = skill_dir
-            .file_name()
-            .unwrap_or_default()
-            .to_string_lossy();
-        let skill_md = skill_dir.join("SKILL.md");
-        let desc = if skill_md.is_file() {
-            let content = std::fs::read_to_string(&skill_md)?;
-            let fm = parse_frontmatter(&content);
-            fm.get("description").cloned().unwrap_or_default()
-        } else {
-            String::new()
+    for skill in &skills {
+        let skill_md = skill.path.join("SKILL.md");
+        let desc = match frontmatter::read::<SkillMeta>(&skill_md) {
+            Ok(meta) => frontmatter::one_line(&meta.unwrap_or_default().description),
+            Err(e) => {
+                eprintln!("  Warning: {:#}", e);
+                String::new()
+            }
         };
-        println!("  {:<24} {}", name, desc);
+        println!("{}", skill.row(reg, &desc));
     }
 
-    if skill_dirs.is_empty() {
+    if skills.is_empty() {
         println!("  (none)");
     }
     Ok(())
 }
 
-fn uninstall(name: &str, target: &Path) -> Result<()> {
-    let target_path = target.join(".claude").join("skills").join(name);
-    installer::uninstall_skill(&target_path)
+fn uninstall(name: &str, claude_dir: &Path) -> Result<()> {
+    let target_path = claude_dir.join("skills").join(name);
+    installer::uninstall_skill(&target_path)?;
+    installer::remove_permissions(None, claude_dir, ItemKind::Skill, name)?;
+    lockfile::forget(claude_dir, ItemKind::Skill, name)
 }
@@ -0,0 +1,139 @@
+use anyhow::{bail, Result};
+
+use crate::cli::SourceAction;
+use crate::config::{self, Config, SourceEntry};
+use crate::git;
+use crate::registry::Registry;
+use crate::transaction;
+
+pub fn run(action: SourceAction) -> Result<()> {
+    match action {
+        SourceAction::Add { name, url, git_ref } => add(&name, &url, git_ref),
+        SourceAction::Update { names } => update(&names),
+        SourceAction::List => list(),
+        SourceAction::Remove { name } => remove(&name),
+    }
+}
+
+fn add(name: &str, url: &str, git_ref: Option<String>) -> Result<()> {
+    if !config::is_valid_name(name) {
+        bail!("Invalid source name '{}' (must be non-empty and contain no '/')", name);
+    }
+    let mut config = Config::load()?;
+    if config.get(name).is_some() {
+        bail!("Source '{}' already exists", name);
+    }
+
+    let dest = config::cache_dir()?.join("sources").join(name);
+    if dest.exists() {
+        bail!("{} already exists; remove it first", dest.display());
+    }
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    println!("Cloning {} into {}", url, dest.display());
+    if let Err(e) = git::clone(url, git_ref.as_deref(), &dest) {
+        transaction::remove_path(&dest)?;
+        return Err(e);
+    }
+
+    config.sources.push(SourceEntry {
+        name: name.to_string(),
+        path: dest.display().to_string(),
+        url: Some(url.to_string()),
+        git_ref,
+    });
+    config.save()?;
+
+    let commit = git::head_commit(&dest).unwrap_or_default();
+    println!("  Added source '{}' at {}", name, short(&commit));
+    println!("\nInstall from it with e.g. `claude-registry skill install {}/<skill> --target ...`", name);
+    Ok(())
+}
+
+fn update(names: &[String]) -> Result<()> {
+    let config = Config::load()?;
+    for name in names {
+        match config.get(name) {
+            None => bail!("Source '{}' not found", name),
+            Some(s) if s.url.is_none() => bail!("Source '{}' is not a git source", name),
+            Some(_) => {}
+        }
+    }
+
+    let sources: Vec<&SourceEntry> = config
+        .sources
+        .iter()
+        .filter(|s| s.url.is_some())
+        .filter(|s| names.is_empty() || names.contains(&s.name))
+        .collect();
+    if sources.is_empty() {
+        println!("No git sources configured");
+        return Ok(());
+    }
+
+    println!("Updating {} source(s)", sources.len());
+    let mut failed = 0usize;
+    for source in &sources {
+        match git::update(&source.root(), source.git_ref.as_deref()) {
+            Ok((before, after)) if before == after => {
+                println!("  {}: up to date ({})", source.name, short(&after));
+            }
+            Ok((before, after)) => {
+                println!("  {}: {}..{}", source.name, short(&before), short(&after));
+            }
+            Err(e) => {
+                eprintln!("  Warning: {}: {}", source.name, e);
+                failed += 1;
+            }
+        }
+    }
+    if failed > 0 {
+        bail!("{} of {} source(s) failed to update", failed, sources.len());
+    }
+    Ok(())
+}
+
+fn list() -> Result<()> {
+    let config = Config::load()?;
+    let reg = Registry::load()?;
+
+    println!("Registry sources (highest precedence first):");
+    println!();
+    for source in &reg.sources {
+        println!("  {:<12} {}", source.name, source.root.display());
+        if let Some(url) = config.get(&source.name).and_then(|s| s.url.as_ref()) {
+            let git_ref = config.get(&source.name).and_then(|s| s.git_ref.as_deref());
+            let commit = git::head_commit(&source.root).unwrap_or_default();
+            println!(
+                "  {:<12} git: {} ({}) @ {}",
+                "",
+                url,
+                git_ref.unwrap_or("default branch"),
+                short(&commit)
+            );
+        }
+    }
+    Ok(())
+}
+
+fn remove(name: &str) -> Result<()> {
+    let mut config = Config::load()?;
+    let Some(pos) = config.sources.iter().position(|s| s.name == name) else {
+        bail!("Source '{}' not found", name);
+    };
+    let source = config.sources.remove(pos);
+    config.save()?;
+
+    // Only clones we made in the cache are deleted, never a user's own checkout
+    let root = source.root();
+    if source.url.is_some() && root.starts_with(config::cache_dir()?) {
+        transaction::remove_path(&root)?;
+    }
+    println!("  Removed source '{}'", name);
+    Ok(())
+}
+
+fn short(commit: &str) -> &str {
+    &commit[..commit.len().min(12)]
+}
@@ -0,0 +1,264 @@
+use anyhow::{bail, Result};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::audit;
+use crate::git;
+use crate::installer::{self, FileChanges, InstallOptions};
+use crate::lockfile::{self, InstallMode, ItemKind, LockEntry, Lockfile};
+use crate::permissions;
+use crate::registry::{self, Registry};
+use crate::signing;
+use crate::transaction::{self, Transaction};
+
+pub fn run(
+    names: &[String],
+    kind: Option<ItemKind>,
+    claude_dir: &Path,
+    opts: &InstallOptions,
+    keep_going: bool,
+) -> Result<()> {
+    let reg = Registry::load()?;
+    let lock = Lockfile::load(claude_dir)?;
+
+    let entries: Vec<&LockEntry> = lock
+        .items
+        .iter()
+        .filter(|e| kind.is_none_or(|k| e.kind == k))
+        .filter(|e| names.is_empty() || names.contains(&e.name))
+        .collect();
+
+    for name in names {
+        if !entries.iter().any(|e| &e.name == name) {
+            bail!(
+                "'{}' is not recorded in {}",
+                name,
+                lockfile::lock_path(claude_dir).display()
+            );
+        }
+    }
+
+    if entries.is_empty() {
+        println!("Nothing to update in {}", claude_dir.display());
+        return Ok(());
+    }
+
+    println!(
+        "Updating {} item(s) in {}",
+        entries.len(),
+        claude_dir.display()
+    );
+
+    let (mut updated, mut failed) = (0usize, 0usize);
+    transaction::apply_all(&entries, keep_going, |tx, entry| {
+        let changes = source_of(&reg, entry)
+            .and_then(|root| update_entry(tx, root, claude_dir, entry, opts))
+            .inspect_err(|_| failed += 1)?;
+        if changes.is_empty() {
+            println!("  {} {}: up to date", entry.kind, entry.name);
+        } else {
+            println!("  {} {}:", entry.kind, entry.name);
+            changes.print();
+            updated += 1;
+        }
+        Ok(())
+    })?;
+    if failed > 0 {
+        bail!("{} of {} item(s) failed to update", failed, entries.len());
+    }
+
+    if opts.dry_run {
+        println!("\nDry run complete. No files were modified.");
+    } else {
+        println!("\nDone! {} item(s) updated.", updated);
+    }
+    Ok(())
+}
+
+/// The root to update `entry` from: the source it was installed from while
+/// that still provides it, otherwise whichever source resolves its name.
+fn source_of<'a>(reg: &'a Registry, entry: &LockEntry) -> Result<&'a Path> {
+    let recorded = reg
+        .by_root(&entry.source)
+        .filter(|s| registry::item_path(&s.root, entry.kind, &entry.name).exists());
+    match recorded {
+        Some(source) => Ok(&source.root),
+        None => Ok(&reg.resolve(entry.kind, &entry.name)?.0.root),
+    }
+}
+
+fn update_entry(
+    tx: &mut Transaction,
+    root: &Path,
+    claude_dir: &Path,
+    entry: &LockEntry,
+    opts: &InstallOptions,
+) -> Result<FileChanges> {
+    let (strategy, dry_run) = (opts.strategy, opts.dry_run);
+    let (content_dir, files) = upstream(root, entry)?;
+    let source_moved = entry.source != root.display().to_string();
+    // New content must pass the same signature check and audit as a fresh install.
+    if files != entry.files || source_moved {
+        signing::check_item(root, entry.kind, &entry.name, &files, opts.allow_unsigned)?;
+        if entry.kind != ItemKind::Agent {
+            audit::check(root, entry.kind, &entry.name, opts.accept_risk)?;
+        }
+    }
+
+    // Hooks keep their permissions in step in install_hook; MCP servers have none.
+    if matches!(entry.kind, ItemKind::Skill | ItemKind::Agent) {
+        let rules = permissions::declared(root, entry.kind, &entry.name)?;
+        let settings = claude_dir.join(lockfile::SETTINGS_FILE);
+        let action = format!("update {} {}", entry.kind, entry.name);
+        installer::sync_permissions(Some(tx), &settings, entry.kind, &entry.name, &rules, &action, dry_run)?;
+    }
+
+    // Linked items always reflect the registry source
+    if entry.mode == InstallMode::Link {
+        return Ok(FileChanges::default());
+    }
+
+    if !dry_run {
+        installer::track_bookkeeping(tx, claude_dir)?;
+    }
+    let changes = match entry.kind {
+        ItemKind::Skill => {
+            let dst = claude_dir.join("skills").join(&entry.name);
+            if !dry_run {
+                tx.track(&dst)?;
+            }
+            let recorded = &entry.files;
+            installer::update_skill(&content_dir, &dst, recorded, claude_dir, strategy, dry_run)?
+        }
+        ItemKind::Agent => {
+            let file = format!("{}.md", entry.name);
+            let dst = claude_dir.join("agents").join(&file);
+            if !dry_run {
+                tx.track(&dst)?;
+            }
+            let src = content_dir.join(&file);
+            installer::update_agent(&src, &dst, &entry.files, claude_dir, strategy, dry_run)?
+        }
+        ItemKind::Hook => {
+            let changes = installer::diff_files(&files, &entry.files, &entry.files);
+            if !changes.is_empty() && !dry_run {
+                let settings = claude_dir.join(entry.settings_file());
+                tx.track(&settings)?;
+                tx.track(&installer::hook_install_dir(&settings, &entry.name))?;
+                installer::install_hook(Some(tx), &content_dir.join("HOOK.json"), &settings, &[], false)?;
+            }
+            changes
+        }
+        ItemKind::Mcp => {
+            let changes = installer::diff_files(&files, &entry.files, &entry.files);
+            if !changes.is_empty() && !dry_run {
+                let config = registry::mcp_config_path(claude_dir);
+                tx.track(&config)?;
+                installer::install_mcp(Some(tx), &content_dir.join("MCP.json"), &config, &[], false, false)?;
+            }
+            changes
+        }
+    };
+
+    let commit_moved = entry.commit != git::head_commit(root);
+    if !dry_run && (files != entry.files || source_moved || commit_moved) {
+        let copy = InstallMode::Copy;
+        lockfile::record(claude_dir, entry.kind, &entry.name, root, copy, &content_dir, files)?;
+    }
+    Ok(changes)
+}
+
+/// The registry content of `entry` under `root`: the directory recorded in the
+/// lockfile and the current hashes of its files.
+fn upstream(root: &Path, entry: &LockEntry) -> Result<(PathBuf, BTreeMap<String, String>)> {
+    let name = &entry.name;
+    match entry.kind {
+        ItemKind::Skill => {
+            let src = registry::skills_dir(root).join(name);
+            if !src.is_dir() {
+                bail!("Skill '{}' no longer exists in registry", src.display());
+            }
+            let files = lockfile::hash_tree(&src)?;
+            Ok((src, files))
+        }
+        ItemKind::Agent => {
+            let agents_dir = registry::agents_dir(root);
+            let src = agents_dir.join(format!("{}.md", name));
+            if !src.is_file() {
+                bail!("Agent '{}' no longer exists in registry", src.display());
+            }
+            Ok((agents_dir, lockfile::hash_single(&src)?))
+        }
+        ItemKind::Hook => {
+            let hook_dir = registry::hooks_dir(root).join(name);
+            if !hook_dir.join("HOOK.json").is_file() {
+                bail!("Hook '{}' no longer exists in registry", name);
+            }
+            let files = lockfile::hash_hook(&hook_dir)?;
+            Ok((hook_dir, files))
+        }
+        ItemKind::Mcp => {
+            let dir = registry::mcp_dir(root).join(name);
+            if !dir.join("MCP.json").is_file() {
+                bail!("MCP server '{}' no longer exists in registry", name);
+            }
+            let files = lockfile::hash_tree(&dir)?;
+            Ok((dir, files))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config;
+    use ed25519_dalek::SigningKey;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn refuses_unsigned_hook_changes_when_keys_are_trusted() {
+        let tmp = TempDir::new().unwrap();
+        let _env = config::isolate(tmp.path());
+        let root = tmp.path().join("registry");
+        let hook_dir = registry::hooks_dir(&root).join("fmt");
+        fs::create_dir_all(&hook_dir).unwrap();
+        let write_hook = |command: &str| {
+            let def = serde_json::json!({"name": "fmt", "description": "d", "hooks": {
+                "Stop": [{"hooks": [{"type": "command", "command": command}]}]
+            }});
+            fs::write(hook_dir.join("HOOK.json"), def.to_string()).unwrap();
+        };
+        write_hook("cargo fmt");
+        let claude_dir = tmp.path().join("project/.claude");
+        let mut tx = Transaction::new();
+        let opts = InstallOptions::default();
+        installer::sync_hook(&mut tx, &root, "fmt", &claude_dir, &opts).unwrap();
+        tx.commit().unwrap();
+        let settings = claude_dir.join(lockfile::SETTINGS_FILE);
+        let installed = fs::read_to_string(&settings).unwrap();
+
+        write_hook("cargo fmt; ./notify.sh");
+        let key = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let trusted = format!("trusted_keys = [\"{}\"]\n", signing::public_key_hex(&key));
+        fs::write(tmp.path().join("config.toml"), trusted).unwrap();
+        let lock = Lockfile::load(&claude_dir).unwrap();
+        let entry = lock.get(ItemKind::Hook, "fmt").unwrap();
+
+        let mut tx = Transaction::new();
+        let err = update_entry(&mut tx, &root, &claude_dir, entry, &opts).unwrap_err();
+        tx.rollback().unwrap();
+        assert!(format!("{:#}", err).contains("Refusing to install hook 'fmt'"), "{:#}", err);
+        assert_eq!(fs::read_to_string(&settings).unwrap(), installed);
+
+        let mut tx = Transaction::new();
+        let opts = InstallOptions {
+            allow_unsigned: true,
+            ..Default::default()
+        };
+        let changes = update_entry(&mut tx, &root, &claude_dir, entry, &opts).unwrap();
+        tx.commit().unwrap();
+        assert_eq!(changes.changed, ["HOOK.json"]);
+        assert!(fs::read_to_string(&settings).unwrap().contains("notify.sh"));
+    }
+}
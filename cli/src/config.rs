@@ -0,0 +1,169 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Return path to the user configuration file.
+/// Priority: CLAUDE_REGISTRY_CONFIG env var > $XDG_CONFIG_HOME/claude-registry/config.toml
+/// > ~/.config/claude-registry/config.toml
+pub fn config_path() -> Result<PathBuf> {
+    if let Ok(path) = std::env::var("CLAUDE_REGISTRY_CONFIG") {
+        return Ok(PathBuf::from(path));
+    }
+    let base = match std::env::var("XDG_CONFIG_HOME") {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => {
+            let home = std::env::var("HOME").context("HOME environment variable not set")?;
+            PathBuf::from(home).join(".config")
+        }
+    };
+    Ok(base.join("claude-registry").join("config.toml"))
+}
+
+/// Return path to the cache directory holding cloned git sources.
+/// Priority: CLAUDE_REGISTRY_CACHE env var > $XDG_CACHE_HOME/claude-registry
+/// > ~/.cache/claude-registry
+pub fn cache_dir() -> Result<PathBuf> {
+    if let Ok(path) = std::env::var("CLAUDE_REGISTRY_CACHE") {
+        return Ok(PathBuf::from(path));
+    }
+    let base = match std::env::var("XDG_CACHE_HOME") {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => {
+            let home = std::env::var("HOME").context("HOME environment variable not set")?;
+            PathBuf::from(home).join(".cache")
+        }
+    };
+    Ok(base.join("claude-registry"))
+}
+
+/// Return path to the state directory (settings.json history).
+/// Priority: CLAUDE_REGISTRY_STATE env var > $XDG_STATE_HOME/claude-registry
+/// > ~/.local/state/claude-registry
+pub fn state_dir() -> Result<PathBuf> {
+    if let Ok(path) = std::env::var("CLAUDE_REGISTRY_STATE") {
+        return Ok(PathBuf::from(path));
+    }
+    let base = match std::env::var("XDG_STATE_HOME") {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => {
+            let home = std::env::var("HOME").context("HOME environment variable not set")?;
+            PathBuf::from(home).join(".local/state")
+        }
+    };
+    Ok(base.join("claude-registry"))
+}
+
+/// Source names qualify item names (`name/item`), so they cannot contain '/'.
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains('/')
+}
+
+/// Contents of config.toml.
+///
+/// ```toml
+/// # Public keys (hex) whose signatures installs accept
+/// trusted_keys = ["3b6a27bcceb6a42d62a3a8d02a6f0d73653215771de243a63ac048a18b59da29"]
+///
+/// [[sources]]
+/// name = "internal"
+/// path = "~/src/internal-registry"
+///
+/// [[sources]]
+/// name = "team"
+/// path = "~/.cache/claude-registry/sources/team"
+/// url = "https://example.com/team/registry.git"
+/// ref = "main"
+/// ```
+#[derive(Serialize, Deserialize, Default, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Ed25519 public keys (hex). When set, installs require content signed by one of them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trusted_keys: Vec<String>,
+    /// Registry roots, highest precedence first.
+    #[serde(default)]
+    pub sources: Vec<SourceEntry>,
+}
+
+/// A configured registry root.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct SourceEntry {
+    pub name: String,
+    pub path: String,
+    /// Remote of a git source; `path` is then its clone in the cache.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Branch or tag a git source tracks.
+    #[serde(default, rename = "ref", skip_serializing_if = "Option::is_none")]
+    pub git_ref: Option<String>,
+}
+
+impl SourceEntry {
+    /// The root directory, with a leading `~/` expanded to $HOME.
+    pub fn root(&self) -> PathBuf {
+        match (self.path.strip_prefix("~/"), std::env::var("HOME")) {
+            (Some(rest), Ok(home)) => PathBuf::from(home).join(rest),
+            _ => PathBuf::from(&self.path),
+        }
+    }
+}
+
+impl Config {
+    /// Load config.toml; return an empty config if it does not exist.
+    pub fn load() -> Result<Self> {
+        Self::load_from(&config_path()?)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let config: Config = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        for (i, source) in config.sources.iter().enumerate() {
+            if !is_valid_name(&source.name) {
+                bail!(
+                    "{}: invalid source name '{}' (must be non-empty and contain no '/')",
+                    path.display(),
+                    source.name
+                );
+            }
+            if config.sources[..i].iter().any(|s| s.name == source.name) {
+                bail!("{}: duplicate source name '{}'", path.display(), source.name);
+            }
+        }
+        Ok(config)
+    }
+
+    /// Write config.toml, creating its directory if needed.
+    pub fn save(&self) -> Result<()> {
+        let path = config_path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let content = toml::to_string_pretty(self)?;
+        fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SourceEntry> {
+        self.sources.iter().find(|s| s.name == name)
+    }
+}
+
+/// Point config.toml and the state directory at `dir` for the rest of a test.
+///
+/// The environment is shared by every test thread, so the returned guard must
+/// be held while the test reads config or writes settings history.
+#[cfg(test)]
+pub fn isolate(dir: &Path) -> std::sync::MutexGuard<'static, ()> {
+    static ENV: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    let guard = ENV.lock().unwrap_or_else(|e| e.into_inner());
+    std::env::set_var("CLAUDE_REGISTRY_CONFIG", dir.join("config.toml"));
+    std::env::set_var("CLAUDE_REGISTRY_STATE", dir.join("state"));
+    guard
+}
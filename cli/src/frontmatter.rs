@@ -1,66 +1,167 @@
-use std::collections::HashMap;
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+use std::fmt;
+use std::path::Path;
 
-/// Parse YAML frontmatter from a markdown file content.
-/// Supports both `>` (folded) and `|` (literal) multiline values.
-pub fn parse_frontmatter(content: &str) -> HashMap<String, String> {
-    let mut map = HashMap::new();
+use crate::permissions::Rules;
 
-    // Extract content between --- delimiters
-    let trimmed = content.trim_start();
-    if !trimmed.starts_with("---") {
-        return map;
+/// Metadata declared in a skill's SKILL.md frontmatter.
+#[derive(Deserialize, Default, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct SkillMeta {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default, deserialize_with = "text")]
+    pub description: String,
+    #[serde(default, deserialize_with = "list")]
+    pub tags: Vec<String>,
+    #[serde(default, deserialize_with = "list")]
+    pub allowed_tools: Vec<String>,
+    #[serde(default, deserialize_with = "scalar")]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub license: Option<String>,
+    #[serde(default)]
+    pub permissions: Rules,
+}
+
+/// Metadata declared in an agent's frontmatter.
+#[derive(Deserialize, Default, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct AgentMeta {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default, deserialize_with = "text")]
+    pub description: String,
+    #[serde(default, deserialize_with = "list")]
+    pub tags: Vec<String>,
+    #[serde(default, deserialize_with = "list")]
+    pub tools: Vec<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default, deserialize_with = "scalar")]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub permissions: Rules,
+}
+
+/// Malformed frontmatter, located by line in the markdown file.
+#[derive(Debug)]
+pub struct FrontmatterError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for FrontmatterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
     }
+}
 
-    let after_first = &trimmed[3..];
-    let end = match after_first.find("\n---") {
-        Some(pos) => pos,
-        None => return map,
-    };
+impl std::error::Error for FrontmatterError {}
 
-    let fm_block = &after_first[..end];
-    let mut current_key: Option<String> = None;
-    let mut is_multiline = false;
-
-    for line in fm_block.lines() {
-        // Continuation of multiline value (indented with spaces)
-        if is_multiline && current_key.is_some() && (line.starts_with("  ") || line.starts_with('\t'))
-        {
-            let key = current_key.as_ref().unwrap();
-            let existing = map.get(key).cloned().unwrap_or_default();
-            let appended = if existing.is_empty() {
-                line.trim().to_string()
-            } else {
-                format!("{} {}", existing, line.trim())
-            };
-            map.insert(key.clone(), appended);
-            continue;
+/// Locate the YAML block between `---` delimiters.
+/// Returns the block and the file line (1-based) of the opening delimiter,
+/// or `None` if the file has no frontmatter.
+fn split(content: &str) -> Result<Option<(&str, usize)>, FrontmatterError> {
+    let mut offset = 0;
+    let mut open: Option<(usize, usize)> = None;
+    for (i, line) in content.split_inclusive('\n').enumerate() {
+        let trimmed = line.trim_end();
+        match open {
+            None if trimmed.is_empty() => {}
+            None if trimmed == "---" => open = Some((i + 1, offset + line.len())),
+            None => return Ok(None),
+            Some((open_line, start)) if trimmed == "---" || trimmed == "..." => {
+                return Ok(Some((&content[start..offset], open_line)));
+            }
+            Some(_) => {}
         }
+        offset += line.len();
+    }
+    match open {
+        Some((line, _)) => Err(FrontmatterError {
+            line,
+            message: "frontmatter is not closed by '---'".to_string(),
+        }),
+        None => Ok(None),
+    }
+}
 
-        // Try to match key: value
-        if let Some(colon_pos) = line.find(':') {
-            let key = line[..colon_pos].trim();
-            if key.is_empty() || key.contains(' ') {
-                current_key = None;
-                is_multiline = false;
-                continue;
-            }
-            let val = line[colon_pos + 1..].trim();
-            if val == ">" || val == "|" {
-                current_key = Some(key.to_string());
-                is_multiline = true;
-                map.insert(key.to_string(), String::new());
-            } else {
-                current_key = Some(key.to_string());
-                is_multiline = false;
-                map.insert(key.to_string(), val.to_string());
-            }
-        } else {
-            current_key = None;
-            is_multiline = false;
+/// Parse the frontmatter of a markdown file into `T`.
+/// Returns `Ok(None)` if the content has no frontmatter.
+pub fn parse<T: DeserializeOwned + Default>(content: &str) -> Result<Option<T>, FrontmatterError> {
+    let (yaml, open_line) = match split(content)? {
+        Some(found) => found,
+        None => return Ok(None),
+    };
+    if yaml.trim().is_empty() {
+        return Ok(Some(T::default()));
+    }
+    serde_yaml::from_str(yaml).map(Some).map_err(|e| {
+        let message = e.to_string();
+        match e.location() {
+            Some(loc) => FrontmatterError {
+                line: open_line + loc.line(),
+                // serde_yaml appends its own (block-relative) position
+                message: match message.rsplit_once(" at line ") {
+                    Some((msg, _)) => msg.to_string(),
+                    None => message,
+                },
+            },
+            None => FrontmatterError { line: open_line, message },
         }
+    })
+}
+
+/// Read and parse the frontmatter of the markdown file at `path`.
+/// Errors are reported as `path:line: message`.
+pub fn read<T: DeserializeOwned + Default>(path: &Path) -> Result<Option<T>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    parse(&content).map_err(|e| anyhow::anyhow!("{}:{}: {}", path.display(), e.line, e.message))
+}
+
+/// Collapse a (possibly multi-line) description onto one line for listings.
+pub fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// A text value; block scalars lose their trailing newline, null becomes "".
+fn text<'de, D: Deserializer<'de>>(d: D) -> Result<String, D::Error> {
+    Ok(Option::<String>::deserialize(d)?.unwrap_or_default().trim().to_string())
+}
+
+/// A YAML list, or a comma-separated string (`tags: a, b`).
+fn list<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum ListOrCsv {
+        List(Vec<String>),
+        Csv(String),
     }
+    Ok(match Option::<ListOrCsv>::deserialize(d)? {
+        Some(ListOrCsv::List(items)) => items,
+        Some(ListOrCsv::Csv(s)) => s
+            .split(',')
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .collect(),
+        None => Vec::new(),
+    })
+}
 
-    map
+/// A scalar kept as written, so `version: 1.0` stays "1.0".
+fn scalar<'de, D: Deserializer<'de>>(d: D) -> Result<Option<String>, D::Error> {
+    use serde::de::Error;
+    match serde_yaml::Value::deserialize(d)? {
+        serde_yaml::Value::Null => Ok(None),
+        serde_yaml::Value::String(s) => Ok(Some(s)),
+        serde_yaml::Value::Number(n) => Ok(Some(n.to_string())),
+        serde_yaml::Value::Bool(b) => Ok(Some(b.to_string())),
+        _ => Err(D::Error::custom("expected a scalar value")),
+    }
 }
 
 #[cfg(test)]
@@ -70,23 +171,45 @@ mod tests {
     #[test]
     fn test_folded_scalar() {
         let content = "---\nname: test\ndescription: >\n  line one\n  line two\ntags: a, b\n---\n# Body";
-        let fm = parse_frontmatter(content);
-        assert_eq!(fm.get("name").unwrap(), "test");
-        assert_eq!(fm.get("description").unwrap(), "line one line two");
-        assert_eq!(fm.get("tags").unwrap(), "a, b");
+        let meta: SkillMeta = parse(content).unwrap().unwrap();
+        assert_eq!(meta.name.as_deref(), Some("test"));
+        assert_eq!(meta.description, "line one line two");
+        assert_eq!(meta.tags, vec!["a", "b"]);
     }
 
     #[test]
     fn test_literal_scalar() {
         let content = "---\nname: agent\ndescription: |\n  first\n  second\ntools: Read, Grep\n---\n";
-        let fm = parse_frontmatter(content);
-        assert_eq!(fm.get("description").unwrap(), "first second");
-        assert_eq!(fm.get("tools").unwrap(), "Read, Grep");
+        let meta: AgentMeta = parse(content).unwrap().unwrap();
+        assert_eq!(meta.description, "first\nsecond");
+        assert_eq!(meta.tools, vec!["Read", "Grep"]);
     }
 
     #[test]
     fn test_no_frontmatter() {
-        let fm = parse_frontmatter("# Just a heading");
-        assert!(fm.is_empty());
+        assert!(parse::<SkillMeta>("# Just a heading").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_lists_quotes_and_nested_keys() {
+        let content = "---\nname: \"quoted\"\ntags:\n  - a\n  - b\nallowed-tools: [Read, Bash]\n\
+                       version: 1.0\nmetadata:\n  name: nested\n---\n";
+        let meta: SkillMeta = parse(content).unwrap().unwrap();
+        assert_eq!(meta.name.as_deref(), Some("quoted"));
+        assert_eq!(meta.tags, vec!["a", "b"]);
+        assert_eq!(meta.allowed_tools, vec!["Read", "Bash"]);
+        assert_eq!(meta.version.as_deref(), Some("1.0"));
+    }
+
+    #[test]
+    fn test_errors_report_file_line() {
+        let content = "\n---\nname: ok\ndescription: [unclosed\n---\n";
+        let err = parse::<SkillMeta>(content).unwrap_err();
+        assert!(err.line >= 4, "line {} for {}", err.line, err.message);
+        assert!(!err.message.contains(" at line "));
+
+        let err = parse::<SkillMeta>("---\nname: x\n").unwrap_err();
+        assert_eq!(err.line, 1);
+        assert!(err.message.contains("not closed"));
     }
 }
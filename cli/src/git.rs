@@ -0,0 +1,128 @@
+use anyhow::{bail, Context, Result};
+use std::path::Path;
+use std::process::Command;
+
+/// Run `git <args>` (in `dir`, if given) and return its trimmed stdout.
+fn git(dir: Option<&Path>, args: &[&str]) -> Result<String> {
+    let mut cmd = Command::new("git");
+    if let Some(dir) = dir {
+        cmd.arg("-C").arg(dir);
+    }
+    let output = cmd
+        .args(args)
+        .output()
+        .context("Failed to run git (is it installed and on PATH?)")?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Refuse a user-supplied value that git would parse as an option.
+fn check_not_option(what: &str, value: &str) -> Result<()> {
+    if value.starts_with('-') {
+        bail!("Invalid {} '{}': must not start with '-'", what, value);
+    }
+    Ok(())
+}
+
+/// Clone `url` into `dest`, checking out `git_ref` (branch or tag) if given.
+pub fn clone(url: &str, git_ref: Option<&str>, dest: &Path) -> Result<()> {
+    check_not_option("git URL", url)?;
+    let dest_str = dest.to_string_lossy();
+    let mut args = vec!["clone", "--quiet"];
+    if let Some(r) = git_ref {
+        check_not_option("ref", r)?;
+        args.extend(["--branch", r]);
+    }
+    args.extend(["--", url, dest_str.as_ref()]);
+    git(None, &args).map(|_| ())
+}
+
+/// Fetch from origin and fast-forward the checkout in `dir`.
+///
+/// Branch checkouts are fast-forwarded to their upstream; a detached checkout
+/// (a tag) is moved to wherever `git_ref` now points. Returns the commits
+/// before and after.
+pub fn update(dir: &Path, git_ref: Option<&str>) -> Result<(String, String)> {
+    let before = git(Some(dir), &["rev-parse", "HEAD"])?;
+    git(Some(dir), &["fetch", "--quiet", "--tags", "--force", "origin"])?;
+    if git(Some(dir), &["symbolic-ref", "-q", "HEAD"]).is_ok() {
+        git(Some(dir), &["merge", "--quiet", "--ff-only", "@{upstream}"])?;
+    } else {
+        let r = git_ref.context("Detached checkout has no ref to update to")?;
+        check_not_option("ref", r)?;
+        git(Some(dir), &["checkout", "--quiet", "--detach", r])?;
+    }
+    let after = git(Some(dir), &["rev-parse", "HEAD"])?;
+    Ok((before, after))
+}
+
+/// The commit checked out in `dir`, if `dir` is the top of a git checkout.
+pub fn head_commit(dir: &Path) -> Option<String> {
+    if !dir.join(".git").exists() {
+        return None;
+    }
+    git(Some(dir), &["rev-parse", "HEAD"]).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn commit(dir: &Path, file: &str, content: &str) {
+        fs::write(dir.join(file), content).unwrap();
+        git(Some(dir), &["add", "-A"]).unwrap();
+        let identity = ["-c", "user.name=test", "-c", "user.email=test@example.com"];
+        let args = [&identity[..], &["commit", "--quiet", "-m", file]].concat();
+        git(Some(dir), &args).unwrap();
+    }
+
+    #[test]
+    fn clone_and_fast_forward_from_local_bare_repo() {
+        let tmp = TempDir::new().unwrap();
+        let work = tmp.path().join("work");
+        fs::create_dir_all(&work).unwrap();
+        git(Some(&work), &["init", "--quiet", "--initial-branch=main"]).unwrap();
+        commit(&work, "a.md", "a");
+
+        let bare = tmp.path().join("registry.git");
+        let bare_str = bare.to_string_lossy();
+        git(None, &["clone", "--quiet", "--bare", &work.to_string_lossy(), &bare_str]).unwrap();
+        git(Some(&work), &["remote", "add", "origin", &bare_str]).unwrap();
+
+        let url = format!("file://{}", bare.display());
+        let cache = tmp.path().join("cache");
+        clone(&url, Some("main"), &cache).unwrap();
+        let first = head_commit(&cache).unwrap();
+
+        commit(&work, "b.md", "b");
+        git(Some(&work), &["push", "--quiet", "origin", "main"]).unwrap();
+
+        let (before, after) = update(&cache, Some("main")).unwrap();
+        assert_eq!(before, first);
+        assert_ne!(after, first);
+        assert!(cache.join("b.md").is_file());
+        assert_eq!(head_commit(&cache), Some(after));
+        assert_eq!(head_commit(&cache.join("missing")), None);
+    }
+
+    #[test]
+    fn refuses_urls_and_refs_that_look_like_options() {
+        let tmp = TempDir::new().unwrap();
+        let dest = tmp.path().join("cache");
+        let marker = tmp.path().join("pwned");
+        let url = format!("--upload-pack=touch {}", marker.display());
+        assert!(clone(&url, None, &dest).is_err());
+        assert!(clone("file:///nowhere", Some("--orphan=x"), &dest).is_err());
+        assert!(update(&dest, Some("-b")).is_err());
+        assert!(!marker.exists());
+        assert!(!dest.exists());
+    }
+}
@@ -0,0 +1,339 @@
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+/// Settings entry key holding the parameter values set at install time.
+pub const PARAMS_KEY: &str = "_registry_params";
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ParamType {
+    String,
+    Integer,
+    Number,
+    Boolean,
+}
+
+impl fmt::Display for ParamType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ParamType::String => "string",
+            ParamType::Integer => "integer",
+            ParamType::Number => "number",
+            ParamType::Boolean => "boolean",
+        })
+    }
+}
+
+impl ParamType {
+    pub fn accepts(self, value: &Value) -> bool {
+        match self {
+            ParamType::String => value.is_string(),
+            ParamType::Integer => value.is_i64() || value.is_u64(),
+            ParamType::Number => value.is_number(),
+            ParamType::Boolean => value.is_boolean(),
+        }
+    }
+
+    /// Parse a `--set` value given on the command line.
+    pub fn parse(self, raw: &str) -> Result<Value> {
+        let value = match self {
+            ParamType::String => Some(Value::String(raw.to_string())),
+            ParamType::Integer => raw.parse::<i64>().ok().map(Value::from),
+            ParamType::Number => raw
+                .parse::<f64>()
+                .ok()
+                .and_then(serde_json::Number::from_f64)
+                .map(Value::Number),
+            ParamType::Boolean => raw.parse::<bool>().ok().map(Value::Bool),
+        };
+        value.with_context(|| format!("'{}' is not a valid {} value", raw, self))
+    }
+}
+
+/// A parameter declared in HOOK.json's `parameters`.
+#[derive(Deserialize, Clone, Debug)]
+pub struct Parameter {
+    #[serde(rename = "type")]
+    pub kind: ParamType,
+    #[serde(default)]
+    pub default: Option<Value>,
+    #[serde(default)]
+    pub description: String,
+}
+
+pub type Parameters = BTreeMap<String, Parameter>;
+
+/// Text a value is substituted as inside a longer string.
+pub fn display(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Apply `--set key=value` pairs on top of `previous` values.
+///
+/// Previous values for parameters the hook no longer declares (or whose type
+/// changed) are dropped, so a hook update never fails on stale settings.
+pub fn merge_overrides(
+    params: &Parameters,
+    previous: Option<&Map<String, Value>>,
+    set: &[(String, String)],
+) -> Result<Map<String, Value>> {
+    let mut overrides: Map<String, Value> = previous
+        .into_iter()
+        .flatten()
+        .filter(|(k, v)| params.get(*k).is_some_and(|p| p.kind.accepts(v)))
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+    for (key, raw) in set {
+        let Some(param) = params.get(key) else {
+            let declared: Vec<&str> = params.keys().map(String::as_str).collect();
+            bail!(
+                "unknown parameter '{}' (declared: {})",
+                key,
+                if declared.is_empty() {
+                    "none".to_string()
+                } else {
+                    declared.join(", ")
+                }
+            );
+        };
+        let value = param
+            .kind
+            .parse(raw)
+            .with_context(|| format!("invalid value for parameter '{}'", key))?;
+        overrides.insert(key.clone(), value);
+    }
+    Ok(overrides)
+}
+
+/// Every parameter's effective value: its override, else its default.
+pub fn resolve(params: &Parameters, overrides: &Map<String, Value>) -> Result<Map<String, Value>> {
+    let mut values = Map::new();
+    for (name, param) in params {
+        match overrides.get(name).or(param.default.as_ref()) {
+            Some(value) => {
+                values.insert(name.clone(), value.clone());
+            }
+            None => bail!(
+                "parameter '{}' has no default; pass --set {}=<{}>",
+                name,
+                name,
+                param.kind
+            ),
+        }
+    }
+    Ok(values)
+}
+
+/// Replace `{{name}}` placeholders in every string below `v`.
+///
+/// A string that is exactly one placeholder becomes the typed value, so
+/// numeric fields such as `timeout` can be parameterised too. In `command`
+/// fields values are shell-quoted (see [`substitute_command`]).
+pub fn substitute(v: &mut Value, values: &Map<String, Value>) {
+    match v {
+        Value::String(s) => {
+            let whole = s
+                .strip_prefix("{{")
+                .and_then(|rest| rest.strip_suffix("}}"))
+                .and_then(|name| values.get(name));
+            if let Some(value) = whole {
+                *v = value.clone();
+                return;
+            }
+            for (name, value) in values {
+                *s = s.replace(&format!("{{{{{}}}}}", name), &display(value));
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| substitute(item, values)),
+        Value::Object(map) => {
+            for (key, item) in map.iter_mut() {
+                match item {
+                    Value::String(command) if key == "command" => {
+                        *command = substitute_command(command, values);
+                    }
+                    _ => substitute(item, values),
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Replace `{{name}}` placeholders in a shell command, quoting each value for
+/// where it appears: inside `'...'`, inside `"..."` or as a bare word. A value
+/// can therefore never end the quotes around it or add shell syntax.
+pub fn substitute_command(command: &str, values: &Map<String, Value>) -> String {
+    let mut out = String::with_capacity(command.len());
+    let mut quote: Option<char> = None;
+    let mut chars = command.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let rest = &command[i..];
+        let placeholder = rest
+            .strip_prefix("{{")
+            .and_then(|after| after.split_once("}}"))
+            .and_then(|(name, _)| Some((name.len(), values.get(name)?)));
+        if let Some((len, value)) = placeholder {
+            out.push_str(&shell_quote(&display(value), quote));
+            // Skip the rest of `{{name}}`.
+            for _ in 0..len + 3 {
+                chars.next();
+            }
+            continue;
+        }
+        out.push(c);
+        match (quote, c) {
+            (None, '\'') | (None, '"') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, '\\') | (Some('"'), '\\') => {
+                if let Some((_, escaped)) = chars.next() {
+                    out.push(escaped);
+                }
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Quote `value` for a shell word in the quoting context `quote`.
+fn shell_quote(value: &str, quote: Option<char>) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c);
+    match quote {
+        Some('\'') => value.replace('\'', r"'\''"),
+        Some(_) => value
+            .chars()
+            .flat_map(|c| match c {
+                '\\' | '"' | '$' | '`' => vec!['\\', c],
+                c => vec![c],
+            })
+            .collect(),
+        None if !value.is_empty() && value.chars().all(safe) => value.to_string(),
+        None => format!("'{}'", value.replace('\'', r"'\''")),
+    }
+}
+
+/// Names of all `{{name}}` placeholders in the strings below `v`.
+pub fn placeholders(v: &Value) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    let mut stack = vec![v];
+    while let Some(v) = stack.pop() {
+        match v {
+            Value::String(s) => {
+                let mut rest = s.as_str();
+                while let Some((_, after)) = rest.split_once("{{") {
+                    let Some((name, tail)) = after.split_once("}}") else {
+                        break;
+                    };
+                    names.insert(name.to_string());
+                    rest = tail;
+                }
+            }
+            Value::Array(items) => stack.extend(items),
+            Value::Object(map) => stack.extend(map.values()),
+            _ => {}
+        }
+    }
+    names
+}
+
+/// `key=value` pairs for display.
+pub fn format_values(values: &Map<String, Value>) -> String {
+    values
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, display(v)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn params() -> Parameters {
+        serde_json::from_value(json!({
+            "message": {"type": "string", "default": "done"},
+            "timeout_ms": {"type": "integer", "default": 5000},
+            "sound": {"type": "boolean"}
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn merges_and_validates_overrides() {
+        let params = params();
+        let previous = json!({"message": "old", "timeout_ms": "stale", "gone": 1});
+        let set = [("sound".to_string(), "true".to_string())];
+        let overrides = merge_overrides(&params, previous.as_object(), &set).unwrap();
+        assert_eq!(
+            Value::Object(overrides.clone()),
+            json!({"message": "old", "sound": true})
+        );
+
+        let values = resolve(&params, &overrides).unwrap();
+        assert_eq!(values["timeout_ms"], 5000);
+        assert!(resolve(&params, &Map::new())
+            .unwrap_err()
+            .to_string()
+            .contains("sound"));
+
+        let bad = [("timeout_ms".to_string(), "soon".to_string())];
+        assert!(merge_overrides(&params, None, &bad).is_err());
+        let unknown = [("volume".to_string(), "1".to_string())];
+        assert!(merge_overrides(&params, None, &unknown)
+            .unwrap_err()
+            .to_string()
+            .contains("unknown"));
+    }
+
+    #[test]
+    fn substitutes_text_and_typed_values() {
+        let values = json!({"message": "hi there", "timeout_ms": 5000})
+            .as_object()
+            .unwrap()
+            .clone();
+        let mut group = json!({
+            "matcher": "",
+            "hooks": [{"command": "notify '{{message}}' -t {{timeout_ms}}", "timeout": "{{timeout_ms}}"}]
+        });
+        substitute(&mut group, &values);
+        assert_eq!(group["hooks"][0]["command"], "notify 'hi there' -t 5000");
+        assert_eq!(group["hooks"][0]["timeout"], 5000);
+        assert!(placeholders(&group).is_empty());
+        let names = placeholders(&json!(["{{a}} {{b}}", {"x": "{{a}}"}, "{{open"]));
+        assert_eq!(names.into_iter().collect::<Vec<_>>(), ["a", "b"]);
+    }
+
+    #[test]
+    fn quotes_values_in_commands() {
+        let values = json!({"message": "It's done; rm -rf ~", "n": 3, "word": "ok"})
+            .as_object()
+            .unwrap()
+            .clone();
+        let run = |command: &str| substitute_command(command, &values);
+        assert_eq!(
+            run("notify '{{message}}' -t {{n}}"),
+            r"notify 'It'\''s done; rm -rf ~' -t 3"
+        );
+        assert_eq!(run("say {{message}}"), r"say 'It'\''s done; rm -rf ~'");
+        assert_eq!(run("echo {{word}}"), "echo ok");
+        let values = json!({"m": "a \"$HOME\" `id` \\"}).as_object().unwrap().clone();
+        assert_eq!(
+            substitute_command(r#"echo "{{m}}" 'x"y' \'{{m}}"#, &values),
+            r#"echo "a \"\$HOME\" \`id\` \\" 'x"y' \''a "$HOME" `id` \'"#
+        );
+        assert_eq!(run("echo '{{missing}}'"), "echo '{{missing}}'");
+
+        let values = json!({"message": "It's"}).as_object().unwrap().clone();
+        let mut group = json!({"hooks": [{"command": "{{message}}", "note": "{{message}}"}]});
+        substitute(&mut group, &values);
+        assert_eq!(group["hooks"][0]["command"], r"'It'\''s'");
+        assert_eq!(group["hooks"][0]["note"], "It's");
+    }
+}
@@ -0,0 +1,223 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde_json::{json, Map, Value};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Timeout Claude Code applies to a hook command without a `timeout` field.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Events whose matcher is compared against the tool name.
+const TOOL_EVENTS: &[&str] = &[
+    "PreToolUse",
+    "PermissionRequest",
+    "PostToolUse",
+    "PostToolUseFailure",
+];
+
+/// The payload field an event's matcher is compared against, if any.
+pub fn matcher_field(event: &str) -> Option<&'static str> {
+    match event {
+        e if TOOL_EVENTS.contains(&e) => Some("tool_name"),
+        "Notification" => Some("notification_type"),
+        "PreCompact" => Some("trigger"),
+        "SessionStart" => Some("source"),
+        "SessionEnd" => Some("reason"),
+        "SubagentStart" => Some("agent_type"),
+        _ => None,
+    }
+}
+
+/// The JSON Claude Code sends on stdin for `event`, with placeholder values.
+pub fn default_payload(event: &str, cwd: &Path) -> Value {
+    let mut payload = json!({
+        "session_id": "hook-test",
+        "transcript_path": "",
+        "cwd": cwd.display().to_string(),
+        "permission_mode": "default",
+        "hook_event_name": event,
+    });
+    let extra = match event {
+        e if TOOL_EVENTS.contains(&e) => {
+            let mut tool = json!({"tool_name": "Bash", "tool_input": {"command": "true"}});
+            if e == "PostToolUse" {
+                tool["tool_response"] = json!({});
+            } else if e == "PostToolUseFailure" {
+                tool["error"] = json!("");
+            }
+            tool
+        }
+        "Notification" => {
+            json!({"message": "Claude needs your input", "notification_type": "idle_prompt"})
+        }
+        "UserPromptSubmit" => json!({"prompt": ""}),
+        "Stop" | "SubagentStop" => json!({"stop_hook_active": false}),
+        "PreCompact" => json!({"trigger": "manual", "custom_instructions": ""}),
+        "SessionStart" => json!({"source": "startup"}),
+        "SessionEnd" => json!({"reason": "other"}),
+        "SubagentStart" => json!({"agent_type": ""}),
+        _ => json!({}),
+    };
+    if let (Some(payload), Value::Object(extra)) = (payload.as_object_mut(), extra) {
+        payload.extend(extra);
+    }
+    payload
+}
+
+/// Whether a group's `matcher` selects `value`.
+///
+/// An empty matcher or `*` matches everything; anything else is a regex that
+/// must match the whole value, so `Edit` does not match `NotebookEdit`.
+pub fn matches(matcher: &str, value: &str) -> Result<bool> {
+    if matcher.is_empty() || matcher == "*" {
+        return Ok(true);
+    }
+    let re = Regex::new(&format!("^(?:{})$", matcher))
+        .with_context(|| format!("Invalid matcher '{}'", matcher))?;
+    Ok(re.is_match(value))
+}
+
+/// What running one hook command produced.
+pub struct Outcome {
+    /// Exit code; `None` when killed by a signal or the timeout.
+    pub code: Option<i32>,
+    pub timed_out: bool,
+    pub elapsed: Duration,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl Outcome {
+    /// The JSON object a successful hook printed to control Claude, if any.
+    pub fn decision(&self) -> Option<Map<String, Value>> {
+        if self.code != Some(0) {
+            return None;
+        }
+        serde_json::from_str(self.stdout.trim()).ok()
+    }
+}
+
+/// Run `command` through the shell like Claude Code does: `payload` on
+/// stdin, `cwd` as working directory and `CLAUDE_PROJECT_DIR` set. The
+/// command is killed once `timeout` has passed.
+pub fn run_command(command: &str, payload: &str, cwd: &Path, timeout: Duration) -> Result<Outcome> {
+    let (shell, flag) = if cfg!(windows) {
+        ("cmd", "/C")
+    } else {
+        ("sh", "-c")
+    };
+    let mut child = Command::new(shell)
+        .arg(flag)
+        .arg(command)
+        .current_dir(cwd)
+        .env("CLAUDE_PROJECT_DIR", cwd)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run `{}`", command))?;
+    let start = Instant::now();
+
+    let mut stdin = child.stdin.take().context("stdin not captured")?;
+    let input = payload.to_string();
+    // A hook that never reads stdin must not block us, so write from a thread.
+    thread::spawn(move || {
+        let _ = stdin.write_all(input.as_bytes());
+    });
+    let stdout = read_in_background(child.stdout.take().context("stdout not captured")?);
+    let stderr = read_in_background(child.stderr.take().context("stderr not captured")?);
+
+    let mut timed_out = false;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break Some(status);
+        }
+        if start.elapsed() >= timeout {
+            timed_out = true;
+            let _ = child.kill();
+            let _ = child.wait();
+            break None;
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+    let elapsed = start.elapsed();
+
+    // Background processes started by the hook may keep the pipes open;
+    // don't wait for them longer than a moment.
+    let collect = |rx: mpsc::Receiver<String>| {
+        rx.recv_timeout(Duration::from_millis(500))
+            .unwrap_or_default()
+    };
+    Ok(Outcome {
+        code: status.and_then(|s| s.code()),
+        timed_out,
+        elapsed,
+        stdout: collect(stdout),
+        stderr: collect(stderr),
+    })
+}
+
+fn read_in_background(mut pipe: impl Read + Send + 'static) -> mpsc::Receiver<String> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = pipe.read_to_end(&mut buf);
+        let _ = tx.send(String::from_utf8_lossy(&buf).into_owned());
+    });
+    rx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn matchers_follow_claude_semantics() {
+        assert!(matches("", "Bash").unwrap());
+        assert!(matches("*", "Bash").unwrap());
+        assert!(matches("Edit|Write", "Write").unwrap());
+        assert!(!matches("Edit", "NotebookEdit").unwrap());
+        assert!(matches("mcp__.*", "mcp__github__search").unwrap());
+        assert!(matches("(", "x").is_err());
+        assert_eq!(
+            default_payload("PreToolUse", Path::new("/p"))["tool_name"],
+            "Bash"
+        );
+        assert_eq!(matcher_field("Stop"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn runs_commands_with_payload_and_timeout() {
+        let tmp = TempDir::new().unwrap();
+        let out = run_command(
+            r#"cat >/dev/null; echo '{"decision": "block"}'; echo warn >&2"#,
+            "{}",
+            tmp.path(),
+            Duration::from_secs(10),
+        )
+        .unwrap();
+        assert_eq!(out.code, Some(0));
+        assert_eq!(out.stderr, "warn\n");
+        assert_eq!(out.decision().unwrap()["decision"], "block");
+
+        let out = run_command(
+            "grep -q hook_event_name && exit 2",
+            "{\"hook_event_name\": \"Stop\"}",
+            tmp.path(),
+            Duration::from_secs(10),
+        )
+        .unwrap();
+        assert_eq!(out.code, Some(2));
+        assert!(out.decision().is_none());
+
+        let out = run_command("sleep 5", "", tmp.path(), Duration::from_millis(100)).unwrap();
+        assert!(out.timed_out);
+        assert_eq!(out.code, None);
+    }
+}
@@ -1,10 +1,23 @@
 use anyhow::{bail, Context, Result};
 use serde::Deserialize;
 use serde_json::{Map, Value};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
+use crate::audit;
+use crate::catalog::CatalogItem;
+use crate::hook_params;
+use crate::jsonc;
+use crate::lockfile::{self, InstallMode, ItemKind};
+use crate::mcp;
+use crate::permissions::{self, Rules};
+use crate::registry;
+use crate::settings_history;
+use crate::signing;
+use crate::transaction::{self, Transaction};
+
 /// Copy a skill directory to the target, excluding .gitkeep files.
 pub fn install_skill(src: &Path, dst: &Path, dry_run: bool) -> Result<()> {
     let name = src
@@ -22,8 +35,17 @@ pub fn install_skill(src: &Path, dst: &Path, dry_run: bool) -> Result<()> {
         return Ok(());
     }
 
-    fs::create_dir_all(dst).with_context(|| format!("Failed to create {}", dst.display()))?;
+    // Stage the copy next to the destination and swap it in, so a failure
+    // halfway through never leaves a half-populated skill directory.
+    let seed = fs::symlink_metadata(dst).is_ok_and(|m| m.is_dir()).then_some(dst);
+    transaction::stage_dir(dst, seed, |stage| copy_skill_files(src, stage))?;
+
+    println!("  Installed skill: {}", name);
+    Ok(())
+}
 
+/// Copy every file of a skill except .gitkeep into `dst`.
+fn copy_skill_files(src: &Path, dst: &Path) -> Result<()> {
     for entry in WalkDir::new(src) {
         let entry = entry?;
         let rel = entry.path().strip_prefix(src)?;
@@ -43,8 +65,6 @@ pub fn install_skill(src: &Path, dst: &Path, dry_run: bool) -> Result<()> {
             fs::copy(entry.path(), &target)?;
         }
     }
-
-    println!("  Installed skill: {}", name);
     Ok(())
 }
 
@@ -65,14 +85,726 @@ pub fn install_agent(src: &Path, dst: &Path, dry_run: bool) -> Result<()> {
         return Ok(());
     }
 
-    if let Some(parent) = dst.parent() {
-        fs::create_dir_all(parent)?;
-    }
-    fs::copy(src, dst)?;
+    transaction::copy_atomic(src, dst)?;
     println!("  Installed agent: {}", name);
     Ok(())
 }
 
+// ─── Updates ──────────────────────────────────────────────────────────────────
+
+/// How to treat files that were modified locally since installation.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, clap::ValueEnum)]
+pub enum Strategy {
+    /// Keep the local version
+    Keep,
+    /// Overwrite with the registry version
+    Theirs,
+    /// Three-way merge text files, writing conflict markers where both sides changed
+    Merge,
+}
+
+/// Per-file differences between an installed item and its registry source.
+#[derive(Default, Debug, PartialEq)]
+pub struct FileChanges {
+    pub added: Vec<String>,
+    pub changed: Vec<String>,
+    pub removed: Vec<String>,
+    /// Locally modified files left untouched.
+    pub kept: Vec<String>,
+    /// Locally modified files merged cleanly with the registry version.
+    pub merged: Vec<String>,
+    /// Locally modified files merged with conflict markers.
+    pub conflicted: Vec<String>,
+}
+
+impl FileChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.changed.is_empty()
+            && self.removed.is_empty()
+            && self.kept.is_empty()
+            && self.merged.is_empty()
+            && self.conflicted.is_empty()
+    }
+
+    /// Print one line per file: `+` added, `~` changed, `-` removed,
+    /// `=` kept, `M` merged, `C` merged with conflicts.
+    pub fn print(&self) {
+        for f in &self.added {
+            println!("    + {}", f);
+        }
+        for f in &self.changed {
+            println!("    ~ {}", f);
+        }
+        for f in &self.removed {
+            println!("    - {}", f);
+        }
+        for f in &self.kept {
+            println!("    = {} (local modification kept)", f);
+        }
+        for f in &self.merged {
+            println!("    M {} (merged)", f);
+        }
+        for f in &self.conflicted {
+            println!("    C {} (conflict markers written)", f);
+        }
+    }
+}
+
+/// Compare registry (`upstream`) hashes with what is on disk (`installed`).
+///
+/// Only files recorded in the lockfile (`recorded`) are reported as removed, so
+/// files the user added to an installed skill are never touched.
+pub fn diff_files(
+    upstream: &BTreeMap<String, String>,
+    installed: &BTreeMap<String, String>,
+    recorded: &BTreeMap<String, String>,
+) -> FileChanges {
+    let mut changes = FileChanges::default();
+    for (path, hash) in upstream {
+        match installed.get(path) {
+            None => changes.added.push(path.clone()),
+            Some(h) if h != hash => changes.changed.push(path.clone()),
+            Some(_) => {}
+        }
+    }
+    for path in recorded.keys() {
+        if !upstream.contains_key(path) && installed.contains_key(path) {
+            changes.removed.push(path.clone());
+        }
+    }
+    changes
+}
+
+/// Three-way merge of text files; `Err` carries the result with conflict markers.
+/// Returns `None` when any side is not valid UTF-8.
+fn merge_text(base: &[u8], ours: &[u8], theirs: &[u8]) -> Option<Result<String, String>> {
+    let base = std::str::from_utf8(base).ok()?;
+    let ours = std::str::from_utf8(ours).ok()?;
+    let theirs = std::str::from_utf8(theirs).ok()?;
+    Some(diffy::merge(base, ours, theirs))
+}
+
+/// Apply registry changes from `src_dir` to `dst_dir`, honoring local modifications.
+///
+/// A file counts as locally modified when its on-disk hash differs from the
+/// hash recorded at installation. Without a `strategy` such files abort the
+/// update before anything is written.
+fn sync_files(
+    src_dir: &Path,
+    dst_dir: &Path,
+    upstream: &BTreeMap<String, String>,
+    recorded: &BTreeMap<String, String>,
+    claude_dir: &Path,
+    strategy: Option<Strategy>,
+    dry_run: bool,
+) -> Result<FileChanges> {
+    let mut installed = BTreeMap::new();
+    for rel in upstream.keys().chain(recorded.keys()) {
+        let path = dst_dir.join(rel);
+        if path.is_file() {
+            installed.insert(rel.clone(), lockfile::hash_file(&path)?);
+        }
+    }
+
+    let mut changes = diff_files(upstream, &installed, recorded);
+    let is_modified = |p: &String| recorded.get(p).is_some_and(|h| installed.get(p) != Some(h));
+    let modified: Vec<String> = changes
+        .changed
+        .iter()
+        .chain(&changes.removed)
+        .filter(|p| is_modified(p))
+        .cloned()
+        .collect();
+
+    let strategy = match strategy {
+        Some(s) => s,
+        None if modified.is_empty() => Strategy::Theirs,
+        None => bail!(
+            "{} has local modifications: {} (use --strategy keep|theirs|merge)",
+            dst_dir.display(),
+            modified.join(", ")
+        ),
+    };
+
+    let mut merged_content = Vec::new();
+    if strategy != Strategy::Theirs {
+        changes.changed.retain(|p| !modified.contains(p));
+        changes.removed.retain(|p| !modified.contains(p));
+        for path in modified {
+            let merged = match (strategy, upstream.contains_key(&path)) {
+                (Strategy::Merge, true) => {
+                    let base = lockfile::load_object(claude_dir, &recorded[&path]);
+                    let ours = fs::read(dst_dir.join(&path))?;
+                    let theirs = fs::read(src_dir.join(&path))?;
+                    base.and_then(|b| merge_text(&b, &ours, &theirs))
+                }
+                _ => None,
+            };
+            match merged {
+                Some(Ok(text)) => {
+                    changes.merged.push(path.clone());
+                    merged_content.push((path, text));
+                }
+                Some(Err(text)) => {
+                    changes.conflicted.push(path.clone());
+                    merged_content.push((path, text));
+                }
+                None => changes.kept.push(path),
+            }
+        }
+    }
+
+    if dry_run {
+        return Ok(changes);
+    }
+
+    for rel in changes.added.iter().chain(&changes.changed) {
+        let target = dst_dir.join(rel);
+        transaction::copy_atomic(&src_dir.join(rel), &target)?;
+        let copied = lockfile::hash_file(&target)?;
+        if copied != upstream[rel] {
+            bail!(
+                "{} does not match its expected hash (expected {}, got {})",
+                src_dir.join(rel).display(),
+                upstream[rel],
+                copied
+            );
+        }
+    }
+    for (rel, text) in &merged_content {
+        transaction::write_atomic(&dst_dir.join(rel), text)?;
+    }
+    for rel in &changes.removed {
+        let target = dst_dir.join(rel);
+        fs::remove_file(&target)
+            .with_context(|| format!("Failed to remove {}", target.display()))?;
+        remove_empty_parents(&target, dst_dir);
+    }
+    Ok(changes)
+}
+
+/// Bring an installed skill directory in line with its registry source.
+///
+/// Added and changed files are copied from `src`; files that were installed
+/// earlier but no longer exist upstream are deleted.
+pub fn update_skill(
+    src: &Path,
+    dst: &Path,
+    recorded: &BTreeMap<String, String>,
+    claude_dir: &Path,
+    strategy: Option<Strategy>,
+    dry_run: bool,
+) -> Result<FileChanges> {
+    if !src.is_dir() {
+        bail!("Skill '{}' no longer exists in registry", src.display());
+    }
+    let upstream = lockfile::hash_tree(src)?;
+    update_skill_files(src, dst, &upstream, recorded, claude_dir, strategy, dry_run)
+}
+
+/// Like [`update_skill`], but the upstream file set is given (from a catalog).
+fn update_skill_files(
+    src: &Path,
+    dst: &Path,
+    upstream: &BTreeMap<String, String>,
+    recorded: &BTreeMap<String, String>,
+    claude_dir: &Path,
+    strategy: Option<Strategy>,
+    dry_run: bool,
+) -> Result<FileChanges> {
+    if dry_run || !dst.is_dir() {
+        return sync_files(src, dst, upstream, recorded, claude_dir, strategy, dry_run);
+    }
+    // Refuse local modifications against `dst` itself, so the error names the
+    // directory the user edited rather than the staging copy.
+    if strategy.is_none() {
+        sync_files(src, dst, upstream, recorded, claude_dir, None, true)?;
+    }
+    transaction::stage_dir(dst, Some(dst), |stage| {
+        sync_files(src, stage, upstream, recorded, claude_dir, strategy, false)
+    })
+}
+
+/// Bring an installed agent file in line with its registry source.
+pub fn update_agent(
+    src: &Path,
+    dst: &Path,
+    recorded: &BTreeMap<String, String>,
+    claude_dir: &Path,
+    strategy: Option<Strategy>,
+    dry_run: bool,
+) -> Result<FileChanges> {
+    if !src.is_file() {
+        bail!("Agent '{}' no longer exists in registry", src.display());
+    }
+    let upstream = lockfile::hash_single(src)?;
+    update_agent_file(src, dst, &upstream, recorded, claude_dir, strategy, dry_run)
+}
+
+/// Like [`update_agent`], but the upstream hash is given (from a catalog).
+fn update_agent_file(
+    src: &Path,
+    dst: &Path,
+    upstream: &BTreeMap<String, String>,
+    recorded: &BTreeMap<String, String>,
+    claude_dir: &Path,
+    strategy: Option<Strategy>,
+    dry_run: bool,
+) -> Result<FileChanges> {
+    let (Some(src_dir), Some(dst_dir)) = (src.parent(), dst.parent()) else {
+        bail!("Invalid agent path {}", dst.display());
+    };
+    sync_files(src_dir, dst_dir, upstream, recorded, claude_dir, strategy, dry_run)
+}
+
+/// Back up everything an install into `claude_dir` may touch besides the item itself.
+pub fn track_bookkeeping(tx: &mut Transaction, claude_dir: &Path) -> Result<()> {
+    tx.track(&lockfile::lock_path(claude_dir))?;
+    tx.track(&claude_dir.join(".registry"))
+}
+
+/// Options shared by every install of a skill or agent.
+#[derive(Clone, Copy, Default)]
+pub struct InstallOptions {
+    pub strategy: Option<Strategy>,
+    pub dry_run: bool,
+    /// Symlink to the registry source instead of copying.
+    pub link: bool,
+    /// Install content that is not signed by a trusted key (with a warning).
+    pub allow_unsigned: bool,
+    /// Install items with high-severity audit findings.
+    pub accept_risk: bool,
+}
+
+/// Install a skill into `<claude_dir>/skills` and record it in the lockfile.
+///
+/// If the lockfile says the skill is already installed it is updated in place,
+/// so locally modified files are detected instead of overwritten. Every path
+/// touched is tracked in `tx` so the caller can roll the install back.
+pub fn sync_skill(
+    tx: &mut Transaction,
+    root: &Path,
+    name: &str,
+    claude_dir: &Path,
+    opts: &InstallOptions,
+) -> Result<()> {
+    let src = registry::skills_dir(root).join(name);
+    if src.is_dir() {
+        signing::check_item(root, ItemKind::Skill, name, &lockfile::hash_tree(&src)?, opts.allow_unsigned)?;
+        audit::check(root, ItemKind::Skill, name, opts.accept_risk)?;
+    }
+    sync_skill_with(tx, root, root, name, None, claude_dir, opts)
+}
+
+/// Install a skill listed in a catalog: exactly the listed files are copied,
+/// and each copy must match the catalog's hash.
+pub fn sync_catalog_skill(
+    tx: &mut Transaction,
+    item: &CatalogItem,
+    claude_dir: &Path,
+    opts: &InstallOptions,
+) -> Result<()> {
+    signing::check_file(&item.signed, opts.allow_unsigned)?;
+    audit::check(&item.root, ItemKind::Skill, &item.name, opts.accept_risk)?;
+    sync_skill_with(tx, &item.root, &item.source, &item.name, Some(&item.files), claude_dir, opts)
+}
+
+fn sync_skill_with(
+    tx: &mut Transaction,
+    root: &Path,
+    source: &Path,
+    name: &str,
+    listed: Option<&BTreeMap<String, String>>,
+    claude_dir: &Path,
+    opts: &InstallOptions,
+) -> Result<()> {
+    let src = registry::skills_dir(root).join(name);
+    let dst = claude_dir.join("skills").join(name);
+    let lock = lockfile::Lockfile::load(claude_dir)?;
+    if !opts.dry_run {
+        tx.track(&dst)?;
+        track_bookkeeping(tx, claude_dir)?;
+    }
+
+    let copied = lock
+        .get(ItemKind::Skill, name)
+        .filter(|e| e.mode == InstallMode::Copy && dst.is_dir());
+    let mode = if opts.link {
+        link_skill(&src, &dst, opts.dry_run)?;
+        InstallMode::Link
+    } else {
+        match (copied, listed) {
+            (Some(entry), None) => {
+                let changes =
+                    update_skill(&src, &dst, &entry.files, claude_dir, opts.strategy, opts.dry_run)?;
+                print_update("skill", name, &changes, opts.dry_run);
+            }
+            (Some(entry), Some(files)) => {
+                let changes = update_skill_files(
+                    &src, &dst, files, &entry.files, claude_dir, opts.strategy, opts.dry_run,
+                )?;
+                print_update("skill", name, &changes, opts.dry_run);
+            }
+            (None, _) => {
+                if !opts.dry_run && transaction::is_symlink(&dst) {
+                    transaction::remove_path(&dst)?;
+                }
+                match listed {
+                    Some(files) if !opts.dry_run => {
+                        update_skill_files(&src, &dst, files, &BTreeMap::new(), claude_dir, None, false)?;
+                        println!("  Installed skill: {}", name);
+                    }
+                    _ => install_skill(&src, &dst, opts.dry_run)?,
+                }
+            }
+        }
+        InstallMode::Copy
+    };
+
+    let rules = permissions::declared(root, ItemKind::Skill, name)?;
+    let settings = claude_dir.join(lockfile::SETTINGS_FILE);
+    let action = format!("skill install {}", name);
+    sync_permissions(Some(tx), &settings, ItemKind::Skill, name, &rules, &action, opts.dry_run)?;
+
+    if !opts.dry_run {
+        let files = match listed {
+            Some(files) => files.clone(),
+            None => lockfile::hash_tree(&src)?,
+        };
+        lockfile::record(claude_dir, ItemKind::Skill, name, source, mode, &src, files)?;
+    }
+    Ok(())
+}
+
+/// Install an agent into `<claude_dir>/agents` and record it in the lockfile.
+///
+/// Behaves like [`sync_skill`] for agents that are already installed.
+pub fn sync_agent(
+    tx: &mut Transaction,
+    root: &Path,
+    name: &str,
+    claude_dir: &Path,
+    opts: &InstallOptions,
+) -> Result<()> {
+    let src = registry::agents_dir(root).join(format!("{}.md", name));
+    if src.is_file() {
+        signing::check_item(root, ItemKind::Agent, name, &lockfile::hash_single(&src)?, opts.allow_unsigned)?;
+    }
+    sync_agent_with(tx, root, root, name, None, claude_dir, opts)
+}
+
+/// Install an agent listed in a catalog, verifying the copy against the catalog's hash.
+pub fn sync_catalog_agent(
+    tx: &mut Transaction,
+    item: &CatalogItem,
+    claude_dir: &Path,
+    opts: &InstallOptions,
+) -> Result<()> {
+    signing::check_file(&item.signed, opts.allow_unsigned)?;
+    sync_agent_with(tx, &item.root, &item.source, &item.name, Some(&item.files), claude_dir, opts)
+}
+
+fn sync_agent_with(
+    tx: &mut Transaction,
+    root: &Path,
+    source: &Path,
+    name: &str,
+    listed: Option<&BTreeMap<String, String>>,
+    claude_dir: &Path,
+    opts: &InstallOptions,
+) -> Result<()> {
+    let agents_dir = registry::agents_dir(root);
+    let src = agents_dir.join(format!("{}.md", name));
+    let dst = claude_dir.join("agents").join(format!("{}.md", name));
+    let lock = lockfile::Lockfile::load(claude_dir)?;
+    if !opts.dry_run {
+        tx.track(&dst)?;
+        track_bookkeeping(tx, claude_dir)?;
+    }
+
+    let copied = lock
+        .get(ItemKind::Agent, name)
+        .filter(|e| e.mode == InstallMode::Copy && dst.is_file());
+    let mode = if opts.link {
+        link_agent(&src, &dst, opts.dry_run)?;
+        InstallMode::Link
+    } else {
+        match (copied, listed) {
+            (Some(entry), None) => {
+                let changes =
+                    update_agent(&src, &dst, &entry.files, claude_dir, opts.strategy, opts.dry_run)?;
+                print_update("agent", name, &changes, opts.dry_run);
+            }
+            (Some(entry), Some(files)) => {
+                let changes = update_agent_file(
+                    &src, &dst, files, &entry.files, claude_dir, opts.strategy, opts.dry_run,
+                )?;
+                print_update("agent", name, &changes, opts.dry_run);
+            }
+            (None, _) => {
+                if !opts.dry_run && transaction::is_symlink(&dst) {
+                    transaction::remove_path(&dst)?;
+                }
+                match listed {
+                    Some(files) if !opts.dry_run => {
+                        update_agent_file(&src, &dst, files, &BTreeMap::new(), claude_dir, None, false)?;
+                        println!("  Installed agent: {}", name);
+                    }
+                    _ => install_agent(&src, &dst, opts.dry_run)?,
+                }
+            }
+        }
+        InstallMode::Copy
+    };
+
+    let rules = permissions::declared(root, ItemKind::Agent, name)?;
+    let settings = claude_dir.join(lockfile::SETTINGS_FILE);
+    let action = format!("agent install {}", name);
+    sync_permissions(Some(tx), &settings, ItemKind::Agent, name, &rules, &action, opts.dry_run)?;
+
+    if !opts.dry_run {
+        let files = match listed {
+            Some(files) => files.clone(),
+            None => lockfile::hash_single(&src)?,
+        };
+        lockfile::record(claude_dir, ItemKind::Agent, name, source, mode, &agents_dir, files)?;
+    }
+    Ok(())
+}
+
+/// Install or refresh a hook in `<claude_dir>/settings.json` (or the settings file it is
+/// recorded in) within `tx`, recording it in the lockfile.
+pub fn sync_hook(
+    tx: &mut Transaction,
+    root: &Path,
+    name: &str,
+    claude_dir: &Path,
+    opts: &InstallOptions,
+) -> Result<()> {
+    let settings = lockfile::hook_settings_path(claude_dir, name)?;
+    sync_hook_into(tx, root, name, &settings, &[], opts)
+}
+
+/// Install a hook into the settings file `settings` within `tx`, filling in
+/// the parameter values `set`, and record it in the lockfile next to it.
+pub fn sync_hook_into(
+    tx: &mut Transaction,
+    root: &Path,
+    name: &str,
+    settings: &Path,
+    set: &[(String, String)],
+    opts: &InstallOptions,
+) -> Result<()> {
+    let dry_run = opts.dry_run;
+    let hook_dir = registry::hooks_dir(root).join(name);
+    let hook_def = hook_dir.join("HOOK.json");
+    if !hook_def.is_file() {
+        bail!("Hook '{}' not found in registry", name);
+    }
+    let files = lockfile::hash_hook(&hook_dir)?;
+    signing::check_item(root, ItemKind::Hook, name, &files, opts.allow_unsigned)?;
+    audit::check(root, ItemKind::Hook, name, opts.accept_risk)?;
+
+    // The lockfile has one entry per hook, shared by settings.json and settings.local.json.
+    let claude_dir = settings.parent().unwrap_or(Path::new("."));
+    let file = settings
+        .file_name()
+        .and_then(|f| f.to_str())
+        .unwrap_or(lockfile::SETTINGS_FILE);
+    if let Some(entry) = lockfile::Lockfile::load(claude_dir)?.get(ItemKind::Hook, name) {
+        if entry.settings_file() != file {
+            bail!(
+                "Hook '{}' is already installed in {}; uninstall it there first",
+                name,
+                claude_dir.join(entry.settings_file()).display()
+            );
+        }
+    }
+    if !dry_run {
+        tx.track(settings)?;
+        tx.track(&hook_install_dir(settings, name))?;
+        track_bookkeeping(tx, claude_dir)?;
+    }
+
+    install_hook(Some(tx), &hook_def, settings, set, dry_run)?;
+
+    if !dry_run {
+        lockfile::record(claude_dir, ItemKind::Hook, name, root, InstallMode::Copy, &hook_dir, files)?;
+        lockfile::set_hook_settings(claude_dir, name, file)?;
+    }
+    Ok(())
+}
+
+/// Install or refresh the servers of MCP definition `name` in the MCP config
+/// of `claude_dir` within `tx`, recording it in the lockfile.
+pub fn sync_mcp(
+    tx: &mut Transaction,
+    root: &Path,
+    name: &str,
+    claude_dir: &Path,
+    opts: &InstallOptions,
+) -> Result<()> {
+    sync_mcp_with(tx, root, name, claude_dir, &[], false, opts)
+}
+
+/// Like [`sync_mcp`], with variable values `env`; with `prompt` the remaining
+/// variables are asked for at the terminal.
+pub fn sync_mcp_with(
+    tx: &mut Transaction,
+    root: &Path,
+    name: &str,
+    claude_dir: &Path,
+    env: &[(String, String)],
+    prompt: bool,
+    opts: &InstallOptions,
+) -> Result<()> {
+    let dry_run = opts.dry_run;
+    let dir = registry::mcp_dir(root).join(name);
+    let def = dir.join("MCP.json");
+    if !def.is_file() {
+        bail!("MCP server '{}' not found in registry", name);
+    }
+    let files = lockfile::hash_tree(&dir)?;
+    signing::check_item(root, ItemKind::Mcp, name, &files, opts.allow_unsigned)?;
+    audit::check(root, ItemKind::Mcp, name, opts.accept_risk)?;
+    let config = registry::mcp_config_path(claude_dir);
+    if !dry_run {
+        tx.track(&config)?;
+        track_bookkeeping(tx, claude_dir)?;
+    }
+
+    install_mcp(Some(tx), &def, &config, env, prompt, dry_run)?;
+
+    if !dry_run {
+        lockfile::record(claude_dir, ItemKind::Mcp, name, root, InstallMode::Copy, &dir, files)?;
+    }
+    Ok(())
+}
+
+/// Uninstall a recorded item within `tx` and drop it from the lockfile.
+///
+/// Items already deleted by hand are only forgotten.
+pub fn remove_item(
+    tx: &mut Transaction,
+    claude_dir: &Path,
+    kind: ItemKind,
+    name: &str,
+    dry_run: bool,
+) -> Result<()> {
+    let path = match kind {
+        ItemKind::Skill => claude_dir.join("skills").join(name),
+        ItemKind::Agent => claude_dir.join("agents").join(format!("{}.md", name)),
+        ItemKind::Hook => lockfile::hook_settings_path(claude_dir, name)?,
+        ItemKind::Mcp => registry::mcp_config_path(claude_dir),
+    };
+    if dry_run {
+        println!("  [dry-run] Would remove {}: {}", kind, name);
+        return Ok(());
+    }
+    tx.track(&path)?;
+    if kind == ItemKind::Hook {
+        tx.track(&hook_install_dir(&path, name))?;
+    }
+    track_bookkeeping(tx, claude_dir)?;
+
+    match kind {
+        ItemKind::Skill if path.is_dir() || transaction::is_symlink(&path) => uninstall_skill(&path)?,
+        ItemKind::Agent if path.is_file() || transaction::is_symlink(&path) => uninstall_agent(&path)?,
+        ItemKind::Hook if hook_installed(name, &path)? => uninstall_hook(Some(tx), name, &path)?,
+        ItemKind::Mcp if mcp_installed(name, &path)? => uninstall_mcp(Some(tx), name, &path)?,
+        _ => println!("  {} {} already removed", kind, name),
+    }
+    if matches!(kind, ItemKind::Skill | ItemKind::Agent) {
+        remove_permissions(Some(tx), claude_dir, kind, name)?;
+    }
+    lockfile::forget(claude_dir, kind, name)
+}
+
+fn print_update(kind: &str, name: &str, changes: &FileChanges, dry_run: bool) {
+    let prefix = if dry_run { "[dry-run] Would update" } else { "Updated" };
+    if changes.is_empty() {
+        println!("  {} {}: already up to date", kind, name);
+    } else {
+        println!("  {} {}: {}", prefix, kind, name);
+        changes.print();
+    }
+}
+
+/// Remove now-empty directories between `path` and `root` (exclusive).
+fn remove_empty_parents(path: &Path, root: &Path) {
+    let mut dir = path.parent();
+    while let Some(d) = dir {
+        if d == root || fs::remove_dir(d).is_err() {
+            break;
+        }
+        dir = d.parent();
+    }
+}
+
+/// Symlink a skill directory into the target instead of copying it.
+pub fn link_skill(src: &Path, dst: &Path, dry_run: bool) -> Result<()> {
+    let name = src
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+
+    if !src.is_dir() {
+        bail!("Skill '{}' not found in registry", name);
+    }
+
+    if dry_run {
+        println!("  [dry-run] Would link skill: {} -> {}", name, src.display());
+        return Ok(());
+    }
+
+    create_link(src, dst)?;
+    println!("  Linked skill: {} -> {}", name, src.display());
+    Ok(())
+}
+
+/// Symlink an agent markdown file into the target instead of copying it.
+pub fn link_agent(src: &Path, dst: &Path, dry_run: bool) -> Result<()> {
+    let name = src
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+
+    if !src.is_file() {
+        bail!("Agent '{}' not found in registry", name);
+    }
+
+    if dry_run {
+        println!("  [dry-run] Would link agent: {} -> {}", name, src.display());
+        return Ok(());
+    }
+
+    create_link(src, dst)?;
+    println!("  Linked agent: {} -> {}", name, src.display());
+    Ok(())
+}
+
+/// Point `dst` at `src`, replacing an existing link but never a real file or directory.
+fn create_link(src: &Path, dst: &Path) -> Result<()> {
+    let src = src
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve {}", src.display()))?;
+    if fs::symlink_metadata(dst).is_ok() && !transaction::is_symlink(dst) {
+        bail!(
+            "{} is already installed as a copy; uninstall it before linking",
+            dst.display()
+        );
+    }
+    transaction::remove_path(dst)?;
+    if let Some(parent) = dst.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    transaction::create_symlink(&src, dst)
+}
+
 /// Remove a skill directory from the target.
 pub fn uninstall_skill(target: &Path) -> Result<()> {
     let name = target
@@ -81,6 +813,13 @@ pub fn uninstall_skill(target: &Path) -> Result<()> {
         .to_string_lossy()
         .to_string();
 
+    // Only the link is removed, never the registry source it points to
+    if transaction::is_symlink(target) {
+        transaction::remove_path(target)?;
+        println!("  Removed link for skill: {}", name);
+        return Ok(());
+    }
+
     if !target.is_dir() {
         bail!("Skill '{}' is not installed", name);
     }
@@ -99,136 +838,352 @@ struct HookDefinition {
     #[serde(default)]
     #[allow(dead_code)]
     tags: String,
+    #[serde(default)]
+    parameters: hook_params::Parameters,
+    #[serde(default)]
+    permissions: Rules,
     hooks: Map<String, Value>,
 }
 
 /// Load settings.json; return empty map if the file does not exist.
-fn load_settings(path: &Path) -> Result<Map<String, Value>> {
-    if !path.exists() {
-        return Ok(Map::new());
-    }
-    let content = fs::read_to_string(path)
-        .with_context(|| format!("Failed to read {}", path.display()))?;
-    let v: Value = serde_json::from_str(&content)
-        .with_context(|| format!("Failed to parse {}", path.display()))?;
-    match v {
+pub fn load_settings(path: &Path) -> Result<Map<String, Value>> {
+    match jsonc::Document::load(path)?.value()? {
         Value::Object(m) => Ok(m),
         _ => bail!("{} is not a JSON object", path.display()),
     }
 }
 
-/// Write settings map back to disk (pretty-printed, UTF-8).
-fn save_settings(path: &Path, settings: &Map<String, Value>) -> Result<()> {
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)
-            .with_context(|| format!("Failed to create {}", parent.display()))?;
+/// Current content of a file; empty if it does not exist.
+fn read_or_empty(path: &Path) -> Result<String> {
+    match path.exists() {
+        true => fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display())),
+        false => Ok(String::new()),
+    }
+}
+
+/// Print the edit a dry run would make to settings.json as a unified diff.
+fn preview_settings(path: &Path, doc: &jsonc::Document) -> Result<()> {
+    print_diff(path, &read_or_empty(path)?, doc.text());
+    Ok(())
+}
+
+/// Write an edited settings.json. Only the edited values change; the rest of
+/// the file is kept as is. The previous version is snapshotted first so
+/// `settings restore` can undo `action`; with `tx` the snapshot is dropped
+/// again on rollback.
+///
+/// A file the registry created is deleted once the edit leaves it empty.
+fn save_settings(
+    tx: Option<&mut Transaction>,
+    path: &Path,
+    doc: &jsonc::Document,
+    action: &str,
+) -> Result<()> {
+    if path.exists() && read_or_empty(path)? == doc.text() {
+        return Ok(());
+    }
+    let created = settings_history::created_by_registry(path)?;
+    let snapshot = settings_history::record(path, action)?;
+    if let Some(tx) = tx {
+        tx.track_created(&settings_history::snapshot_file(&snapshot.id)?);
+    }
+    if created && doc.value()? == Value::Object(Map::new()) {
+        transaction::remove_path(path)?;
+        println!("  Removed {}, which is now empty", path.display());
+    } else {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        fs::write(path, doc.text())
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    println!("  Previous settings saved as snapshot {}", snapshot.id);
+    Ok(())
+}
+
+/// Print a unified diff of a file edit, indented under the current item.
+pub fn print_diff(path: &Path, before: &str, after: &str) {
+    if before == after {
+        println!("    {}: no changes", path.display());
+        return;
+    }
+    let patch = diffy::DiffOptions::new()
+        .set_original_filename(path.display().to_string())
+        .set_modified_filename(path.display().to_string())
+        .create_patch(before, after)
+        .to_string();
+    for line in patch.lines() {
+        println!("    {}", line);
+    }
+}
+
+/// Make the permission rules `kind` `name` added to `settings` match `rules`.
+///
+/// Hooks keep theirs in [`install_hook`]; this is for skills and agents,
+/// whose rules go to the settings.json next to where they are installed.
+/// `tx` tracks the file before it is written.
+pub fn sync_permissions(
+    tx: Option<&mut Transaction>,
+    settings: &Path,
+    kind: ItemKind,
+    name: &str,
+    rules: &Rules,
+    action: &str,
+    dry_run: bool,
+) -> Result<()> {
+    if rules.is_empty() && !settings.exists() {
+        return Ok(());
+    }
+    let mut doc = jsonc::Document::load(settings)?;
+    let before = doc.text().to_string();
+    let changes = permissions::apply(&mut doc, &permissions::owner(kind, name), rules)
+        .with_context(|| format!("Failed to update permissions in {}", settings.display()))?;
+    if doc.text() == before {
+        return Ok(());
+    }
+    if dry_run {
+        println!("  [dry-run] Would change permissions in {}: {}", settings.display(), changes.join(", "));
+        return Ok(());
+    }
+    let mut tx = tx;
+    if let Some(tx) = tx.as_deref_mut() {
+        tx.track(settings)?;
+    }
+    save_settings(tx, settings, &doc, action)?;
+    if !changes.is_empty() {
+        println!("  permissions: {}", changes.join(", "));
     }
-    let content = serde_json::to_string_pretty(&Value::Object(settings.clone()))?;
-    fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))?;
     Ok(())
 }
 
+/// Drop the permission rules an uninstalled skill or agent added to `<claude_dir>/settings.json`.
+pub fn remove_permissions(
+    tx: Option<&mut Transaction>,
+    claude_dir: &Path,
+    kind: ItemKind,
+    name: &str,
+) -> Result<()> {
+    let settings = claude_dir.join(lockfile::SETTINGS_FILE);
+    let action = format!("{} uninstall {}", kind, name);
+    sync_permissions(tx, &settings, kind, name, &Rules::default(), &action, false)
+}
+
+/// True if a settings.json hook group belongs to registry hook `name`.
+fn tagged(entry: &Value, name: &str) -> bool {
+    entry.get("_registry_id").and_then(|v| v.as_str()) == Some(name)
+}
+
+/// Placeholder in hook commands that is replaced with the installed hook directory.
+pub const HOOK_DIR_PLACEHOLDER: &str = "${HOOK_DIR}";
+
+/// Where the scripts of hook `name` are installed: `hooks/<name>` next to settings.json.
+pub fn hook_install_dir(settings_path: &Path, name: &str) -> PathBuf {
+    settings_path
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join("hooks")
+        .join(name)
+}
+
+/// Replace [`HOOK_DIR_PLACEHOLDER`] in every command of a matcher group.
+pub fn expand_hook_dir(group: &mut Value, dir: &Path) {
+    let hooks = group.get_mut("hooks").and_then(Value::as_array_mut);
+    for hook in hooks.into_iter().flatten() {
+        if let Some(Value::String(command)) = hook.get_mut("command") {
+            *command = command.replace(HOOK_DIR_PLACEHOLDER, &dir.display().to_string());
+        }
+    }
+}
+
 /// Install a hook definition into settings.json (idempotent).
 ///
 /// For each event key in HOOK.json, existing entries tagged with
 /// `_registry_id == name` are removed, then the new entries (with the tag
-/// appended) are added.
-pub fn install_hook(hook_def_path: &Path, settings_path: &Path, dry_run: bool) -> Result<()> {
+/// appended) are added. The hook's `scripts/` are copied to
+/// [`hook_install_dir`] and `${HOOK_DIR}` in commands points there.
+///
+/// `{{param}}` placeholders are filled from `set` (`key=value` pairs), then
+/// from the values recorded by a previous install, then from the defaults
+/// declared in HOOK.json. Only explicitly set values are recorded under
+/// `_registry_params`, so unset parameters follow the hook's defaults.
+///
+/// With `tx`, the settings snapshot taken before the edit is dropped again
+/// if the transaction is rolled back.
+pub fn install_hook(
+    tx: Option<&mut Transaction>,
+    hook_def_path: &Path,
+    settings_path: &Path,
+    set: &[(String, String)],
+    dry_run: bool,
+) -> Result<()> {
     let content = fs::read_to_string(hook_def_path)
         .with_context(|| format!("Failed to read {}", hook_def_path.display()))?;
     let def: HookDefinition = serde_json::from_str(&content)
         .with_context(|| format!("Failed to parse {}", hook_def_path.display()))?;
+    let previous = installed_params(&def.name, settings_path)?;
+    let overrides = hook_params::merge_overrides(&def.parameters, previous.as_ref(), set)
+        .with_context(|| format!("Invalid parameters for hook '{}'", def.name))?;
+    let values = hook_params::resolve(&def.parameters, &overrides)
+        .with_context(|| format!("Invalid parameters for hook '{}'", def.name))?;
+    let scripts = hook_def_path.with_file_name("scripts");
+    let script_files = match scripts.is_dir() {
+        true => lockfile::hash_tree(&scripts)?,
+        false => BTreeMap::new(),
+    };
+    let installed = hook_install_dir(settings_path, &def.name);
 
-    if dry_run {
-        println!("  [dry-run] Would install hook '{}' into {}", def.name, settings_path.display());
-        for (event, groups) in &def.hooks {
-            println!("    event: {} ({} group(s))", event, groups.as_array().map_or(0, |a| a.len()));
-        }
-        return Ok(());
+    let mut doc = jsonc::Document::load(settings_path)?;
+    if doc.get(&["hooks"])?.is_some_and(|h| !h.is_object()) {
+        bail!("settings.json 'hooks' field is not an object");
     }
-
-    let mut settings = load_settings(settings_path)?;
-
-    // Ensure top-level "hooks" key exists as an object
-    let hooks_obj = settings
-        .entry("hooks")
-        .or_insert_with(|| Value::Object(Map::new()))
-        .as_object_mut()
-        .context("settings.json 'hooks' field is not an object")?;
-
     for (event, new_groups_val) in &def.hooks {
         let new_groups = new_groups_val
             .as_array()
             .with_context(|| format!("hooks.{} must be an array", event))?;
+        let path = ["hooks", event.as_str()];
+        if doc.get(&path)?.is_some_and(|groups| !groups.is_array()) {
+            bail!("hooks.{} is not an array in settings.json", event);
+        }
 
-        // Get or create the array for this event
-        let arr = hooks_obj
-            .entry(event.clone())
-            .or_insert_with(|| Value::Array(Vec::new()))
-            .as_array_mut()
-            .with_context(|| format!("hooks.{} is not an array in settings.json", event))?;
-
-        // Remove existing entries for this registry id
-        arr.retain(|entry| {
-            entry
-                .get("_registry_id")
-                .and_then(|v| v.as_str())
-                .map_or(true, |id| id != def.name)
-        });
-
-        // Append new entries with _registry_id tag
-        for group in new_groups {
-            let mut g = group.clone();
-            if let Some(obj) = g.as_object_mut() {
-                obj.insert("_registry_id".to_string(), Value::String(def.name.clone()));
-            }
-            arr.push(g);
+        // Replace existing entries for this registry id with the new ones
+        doc.remove_items(&path, |entry| tagged(entry, &def.name))?;
+        let groups: Vec<Value> = new_groups
+            .iter()
+            .map(|group| {
+                let mut g = group.clone();
+                expand_hook_dir(&mut g, &installed);
+                hook_params::substitute(&mut g, &values);
+                if let Some(obj) = g.as_object_mut() {
+                    obj.insert("_registry_id".to_string(), Value::String(def.name.clone()));
+                    if !overrides.is_empty() {
+                        obj.insert(
+                            hook_params::PARAMS_KEY.to_string(),
+                            Value::Object(overrides.clone()),
+                        );
+                    }
+                }
+                g
+            })
+            .collect();
+        doc.push_items(&path, &groups)?;
+    }
+    let owner = permissions::owner(ItemKind::Hook, &def.name);
+    let rule_changes = permissions::apply(&mut doc, &owner, &def.permissions)?;
+
+    if dry_run {
+        println!("  [dry-run] Would install hook '{}' into {}", def.name, settings_path.display());
+        if !script_files.is_empty() {
+            println!("    scripts: {} file(s) to {}", script_files.len(), installed.display());
+        }
+        if !values.is_empty() {
+            println!("    parameters: {}", hook_params::format_values(&values));
         }
+        if !rule_changes.is_empty() {
+            println!("    permissions: {}", rule_changes.join(", "));
+        }
+        return preview_settings(settings_path, &doc);
     }
 
-    save_settings(settings_path, &settings)?;
+    if script_files.is_empty() {
+        transaction::remove_path(&installed)?;
+    } else {
+        transaction::stage_dir(&installed, None, |stage| {
+            copy_skill_files(&scripts, &stage.join("scripts"))
+        })?;
+    }
+    save_settings(tx, settings_path, &doc, &format!("hook install {}", def.name))?;
     println!(
         "  Installed hook '{}' into {}",
         def.name,
         settings_path.display()
     );
     println!("  description: {}", def.description);
+    if !script_files.is_empty() {
+        println!("  Copied {} script(s) to {}", script_files.len(), installed.display());
+    }
+    if !values.is_empty() {
+        println!("  parameters: {}", hook_params::format_values(&values));
+    }
+    if !rule_changes.is_empty() {
+        println!("  permissions: {}", rule_changes.join(", "));
+    }
     Ok(())
 }
 
+/// The `_registry_params` recorded by the installed hook `name`, if any.
+pub fn installed_params(name: &str, settings_path: &Path) -> Result<Option<Map<String, Value>>> {
+    let settings = load_settings(settings_path)?;
+    let hooks = settings.get("hooks").and_then(|v| v.as_object());
+    Ok(hooks
+        .into_iter()
+        .flat_map(|h| h.values())
+        .filter_map(|arr| arr.as_array())
+        .flatten()
+        .filter(|entry| tagged(entry, name))
+        .find_map(|entry| entry.get(hook_params::PARAMS_KEY))
+        .and_then(|v| v.as_object())
+        .cloned())
+}
+
+/// True if settings.json contains any entry tagged with `_registry_id == name`.
+pub fn hook_installed(name: &str, settings_path: &Path) -> Result<bool> {
+    let settings = load_settings(settings_path)?;
+    let hooks = settings.get("hooks").and_then(|v| v.as_object());
+    Ok(hooks.is_some_and(|h| {
+        h.values()
+            .filter_map(|arr| arr.as_array())
+            .flatten()
+            .any(|entry| tagged(entry, name))
+    }))
+}
+
 /// Remove all hook entries tagged with `name` from settings.json.
-pub fn uninstall_hook(name: &str, settings_path: &Path) -> Result<()> {
+pub fn uninstall_hook(tx: Option<&mut Transaction>, name: &str, settings_path: &Path) -> Result<()> {
     if !settings_path.exists() {
         bail!("Hook '{}' is not installed (settings.json not found)", name);
     }
 
-    let mut settings = load_settings(settings_path)?;
-
-    let hooks_obj = match settings.get_mut("hooks").and_then(|v| v.as_object_mut()) {
-        Some(obj) => obj,
-        None => bail!("Hook '{}' is not installed (no hooks in settings.json)", name),
+    let mut doc = jsonc::Document::load(settings_path)?;
+    let events: Vec<String> = match doc.get(&["hooks"])? {
+        Some(Value::Object(hooks)) => hooks.keys().cloned().collect(),
+        _ => bail!("Hook '{}' is not installed (no hooks in settings.json)", name),
     };
 
+    // Events (and `hooks`) left empty by the removal are dropped too, so an
+    // install followed by an uninstall leaves settings.json as it was.
     let mut removed = 0usize;
-    for arr_val in hooks_obj.values_mut() {
-        if let Some(arr) = arr_val.as_array_mut() {
-            let before = arr.len();
-            arr.retain(|entry| {
-                entry
-                    .get("_registry_id")
-                    .and_then(|v| v.as_str())
-                    .map_or(true, |id| id != name)
-            });
-            removed += before - arr.len();
+    for event in &events {
+        let path = ["hooks", event.as_str()];
+        if doc.get(&path)?.is_some_and(|groups| groups.is_array()) {
+            let n = doc.remove_items(&path, |entry| tagged(entry, name))?;
+            if n > 0 && doc.get(&path)?.is_some_and(|g| g.as_array().is_some_and(Vec::is_empty)) {
+                doc.remove(&path)?;
+            }
+            removed += n;
         }
     }
+    if removed > 0 && doc.get(&["hooks"])?.is_some_and(|h| h.as_object().is_some_and(Map::is_empty)) {
+        doc.remove(&["hooks"])?;
+    }
 
     if removed == 0 {
         bail!("Hook '{}' is not installed", name);
     }
+    let owner = permissions::owner(ItemKind::Hook, name);
+    let rule_changes = permissions::apply(&mut doc, &owner, &Rules::default())?;
 
-    save_settings(settings_path, &settings)?;
+    save_settings(tx, settings_path, &doc, &format!("hook uninstall {}", name))?;
     println!("  Uninstalled hook '{}' from {}", name, settings_path.display());
+    if !rule_changes.is_empty() {
+        println!("  permissions: {}", rule_changes.join(", "));
+    }
+    let installed = hook_install_dir(settings_path, name);
+    if installed.exists() {
+        transaction::remove_path(&installed)?;
+        remove_empty_parents(&installed, settings_path.parent().unwrap_or(Path::new(".")));
+        println!("  Removed scripts from {}", installed.display());
+    }
     Ok(())
 }
 
@@ -240,6 +1195,12 @@ pub fn uninstall_agent(target: &Path) -> Result<()> {
         .to_string_lossy()
         .to_string();
 
+    if transaction::is_symlink(target) {
+        transaction::remove_path(target)?;
+        println!("  Removed link for agent: {}", name);
+        return Ok(());
+    }
+
     if !target.is_file() {
         bail!("Agent '{}' is not installed", name);
     }
@@ -248,3 +1209,439 @@ pub fn uninstall_agent(target: &Path) -> Result<()> {
     println!("  Uninstalled agent: {}", name);
     Ok(())
 }
+
+// ─── MCP server installer ─────────────────────────────────────────────────────
+
+/// The `mcpServers` of an MCP config, checked to be an object.
+fn mcp_servers(doc: &jsonc::Document, config_path: &Path) -> Result<Map<String, Value>> {
+    match doc.get(&["mcpServers"])? {
+        None => Ok(Map::new()),
+        Some(Value::Object(servers)) => Ok(servers),
+        Some(_) => bail!("{}: 'mcpServers' is not an object", config_path.display()),
+    }
+}
+
+/// Install the servers of an MCP.json into an MCP config (idempotent).
+///
+/// Servers tagged with `_registry_id == name` are replaced; a server of the
+/// same name added by hand or by another item is never overwritten.
+///
+/// `${VAR}` placeholders of declared variables are filled from `env`
+/// (`NAME=value` pairs), then from the values recorded by a previous
+/// install, then, with `prompt`, from answers typed at the terminal. Only
+/// these values are recorded under `_registry_env`; variables without one
+/// keep their placeholder and are read from the environment at startup.
+pub fn install_mcp(
+    tx: Option<&mut Transaction>,
+    def_path: &Path,
+    config_path: &Path,
+    env: &[(String, String)],
+    prompt: bool,
+    dry_run: bool,
+) -> Result<()> {
+    let def = mcp::load(def_path)?;
+    let mut doc = jsonc::Document::load(config_path)?;
+    let existing = mcp_servers(&doc, config_path)?;
+
+    for server in def.servers.keys() {
+        match existing.get(server).map(|s| s.get(mcp::OWNER_KEY).and_then(Value::as_str)) {
+            Some(Some(owner)) if owner != def.name => bail!(
+                "MCP server '{}' in {} was installed by '{}'; uninstall it first",
+                server,
+                config_path.display(),
+                owner
+            ),
+            Some(None) => bail!(
+                "MCP server '{}' already exists in {} and was not installed by the registry",
+                server,
+                config_path.display()
+            ),
+            _ => {}
+        }
+    }
+    let previous = existing
+        .values()
+        .filter(|s| mcp::owned_by(s, &def.name))
+        .find_map(|s| s.get(mcp::ENV_KEY))
+        .and_then(Value::as_object);
+    let values = mcp::resolve_env(&def.variables, previous, env, prompt)
+        .with_context(|| format!("Invalid variables for MCP server '{}'", def.name))?;
+
+    // Servers this item no longer defines go; the others are replaced in place.
+    for (server, entry) in &existing {
+        if mcp::owned_by(entry, &def.name) && !def.servers.contains_key(server) {
+            doc.remove(&["mcpServers", server])?;
+        }
+    }
+    for (server, entry) in &def.servers {
+        let mut entry = entry.clone();
+        mcp::substitute(&mut entry, &def.variables, &values);
+        if let Some(obj) = entry.as_object_mut() {
+            obj.insert(mcp::OWNER_KEY.to_string(), Value::String(def.name.clone()));
+            if !values.is_empty() {
+                obj.insert(mcp::ENV_KEY.to_string(), serde_json::to_value(&values)?);
+            }
+        }
+        doc.set(&["mcpServers", server], &entry)?;
+    }
+
+    let servers: Vec<&str> = def.servers.keys().map(String::as_str).collect();
+    let shared = config_path.file_name().is_some_and(|f| f == ".mcp.json");
+    let secrets: Vec<&str> = def
+        .variables
+        .iter()
+        .filter(|(name, var)| var.secret && values.contains_key(*name))
+        .map(|(name, _)| name.as_str())
+        .collect();
+    if shared && !secrets.is_empty() {
+        eprintln!(
+            "  Warning: {} written into {}, which is usually committed; \
+             leave secrets unset to read them from the environment",
+            secrets.join(", "),
+            config_path.display()
+        );
+    }
+
+    if dry_run {
+        println!(
+            "  [dry-run] Would install MCP server(s) {} into {}",
+            servers.join(", "),
+            config_path.display()
+        );
+        if !def.variables.is_empty() {
+            println!("    variables: {}", mcp::format_env(&def.variables, &values));
+        }
+        return preview_settings(config_path, &doc);
+    }
+
+    save_settings(tx, config_path, &doc, &format!("mcp install {}", def.name))?;
+    println!(
+        "  Installed MCP server(s) {} into {}",
+        servers.join(", "),
+        config_path.display()
+    );
+    println!("  description: {}", def.description);
+    if !def.variables.is_empty() {
+        println!("  variables: {}", mcp::format_env(&def.variables, &values));
+    }
+    Ok(())
+}
+
+/// True if the MCP config contains a server tagged with `_registry_id == name`.
+pub fn mcp_installed(name: &str, config_path: &Path) -> Result<bool> {
+    let config = load_settings(config_path)?;
+    let servers = config.get("mcpServers").and_then(Value::as_object);
+    Ok(servers.is_some_and(|s| s.values().any(|server| mcp::owned_by(server, name))))
+}
+
+/// Remove every server tagged with `name` from an MCP config.
+pub fn uninstall_mcp(tx: Option<&mut Transaction>, name: &str, config_path: &Path) -> Result<()> {
+    if !config_path.exists() {
+        bail!("MCP server '{}' is not installed ({} not found)", name, config_path.display());
+    }
+    let mut doc = jsonc::Document::load(config_path)?;
+    let servers: Vec<String> = mcp_servers(&doc, config_path)?
+        .into_iter()
+        .filter(|(_, server)| mcp::owned_by(server, name))
+        .map(|(server, _)| server)
+        .collect();
+    if servers.is_empty() {
+        bail!("MCP server '{}' is not installed in {}", name, config_path.display());
+    }
+    for server in &servers {
+        doc.remove(&["mcpServers", server])?;
+    }
+    // Like hooks, an `mcpServers` the removal left empty is dropped.
+    if doc.get(&["mcpServers"])? == Some(Value::Object(Map::new())) {
+        doc.remove(&["mcpServers"])?;
+    }
+
+    save_settings(tx, config_path, &doc, &format!("mcp uninstall {}", name))?;
+    println!(
+        "  Uninstalled MCP server(s) {} from {}",
+        servers.join(", "),
+        config_path.display()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    fn map(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn diff_files_only_removes_recorded_files() {
+        let upstream = map(&[("SKILL.md", "new"), ("references/a.md", "a")]);
+        let installed = map(&[("SKILL.md", "old"), ("old.md", "x"), ("mine.md", "y")]);
+        let recorded = map(&[("SKILL.md", "old"), ("old.md", "x")]);
+
+        let changes = diff_files(&upstream, &installed, &recorded);
+        assert_eq!(changes.added, vec!["references/a.md"]);
+        assert_eq!(changes.changed, vec!["SKILL.md"]);
+        assert_eq!(changes.removed, vec!["old.md"]);
+    }
+
+    #[test]
+    fn update_skill_copies_and_prunes() {
+        let tmp = TempDir::new().unwrap();
+        let src = tmp.path().join("src");
+        let dst = tmp.path().join("dst");
+        fs::create_dir_all(src.join("references")).unwrap();
+        fs::write(src.join("SKILL.md"), "v2").unwrap();
+        fs::write(src.join("references/new.md"), "new").unwrap();
+        fs::create_dir_all(dst.join("scripts")).unwrap();
+        fs::write(dst.join("SKILL.md"), "v1").unwrap();
+        fs::write(dst.join("scripts/gone.sh"), "old").unwrap();
+
+        let recorded = lockfile::hash_tree(&dst).unwrap();
+        let claude_dir = tmp.path().join(".claude");
+        let changes = update_skill(&src, &dst, &recorded, &claude_dir, None, false).unwrap();
+
+        assert_eq!(changes.removed, vec!["scripts/gone.sh"]);
+        assert_eq!(fs::read_to_string(dst.join("SKILL.md")).unwrap(), "v2");
+        assert!(dst.join("references/new.md").is_file());
+        assert!(!dst.join("scripts").exists());
+    }
+
+    fn modified_skill(tmp: &TempDir) -> (PathBuf, PathBuf, PathBuf, BTreeMap<String, String>) {
+        let src = tmp.path().join("src");
+        let dst = tmp.path().join("dst");
+        let claude_dir = tmp.path().join(".claude");
+        fs::create_dir_all(&src).unwrap();
+        fs::write(src.join("SKILL.md"), "a\nb\nc\n").unwrap();
+        install_skill(&src, &dst, false).unwrap();
+        let files = lockfile::hash_tree(&src).unwrap();
+        let copy = InstallMode::Copy;
+        lockfile::record(&claude_dir, ItemKind::Skill, "s", tmp.path(), copy, &src, files.clone())
+            .unwrap();
+
+        fs::write(src.join("SKILL.md"), "a\nb\nc-upstream\n").unwrap();
+        fs::write(dst.join("SKILL.md"), "a-local\nb\nc\n").unwrap();
+        (src, dst, claude_dir, files)
+    }
+
+    #[test]
+    fn update_skill_refuses_local_modifications_without_strategy() {
+        let tmp = TempDir::new().unwrap();
+        let (src, dst, claude_dir, recorded) = modified_skill(&tmp);
+
+        let err = update_skill(&src, &dst, &recorded, &claude_dir, None, false).unwrap_err();
+        let expected = format!("{} has local modifications: SKILL.md", dst.display());
+        assert!(err.to_string().starts_with(&expected), "{}", err);
+        assert_eq!(fs::read_to_string(dst.join("SKILL.md")).unwrap(), "a-local\nb\nc\n");
+
+        let changes =
+            update_skill(&src, &dst, &recorded, &claude_dir, Some(Strategy::Keep), false).unwrap();
+        assert_eq!(changes.kept, vec!["SKILL.md"]);
+        assert_eq!(fs::read_to_string(dst.join("SKILL.md")).unwrap(), "a-local\nb\nc\n");
+    }
+
+    #[test]
+    fn update_skill_merges_local_and_upstream_edits() {
+        let tmp = TempDir::new().unwrap();
+        let (src, dst, claude_dir, recorded) = modified_skill(&tmp);
+
+        let changes =
+            update_skill(&src, &dst, &recorded, &claude_dir, Some(Strategy::Merge), false).unwrap();
+        assert_eq!(changes.merged, vec!["SKILL.md"]);
+        assert_eq!(
+            fs::read_to_string(dst.join("SKILL.md")).unwrap(),
+            "a-local\nb\nc-upstream\n"
+        );
+    }
+
+    #[test]
+    fn update_skill_writes_conflict_markers() {
+        let tmp = TempDir::new().unwrap();
+        let (src, dst, claude_dir, recorded) = modified_skill(&tmp);
+        fs::write(dst.join("SKILL.md"), "a\nb\nc-local\n").unwrap();
+
+        let changes =
+            update_skill(&src, &dst, &recorded, &claude_dir, Some(Strategy::Merge), false).unwrap();
+        assert_eq!(changes.conflicted, vec!["SKILL.md"]);
+        let merged = fs::read_to_string(dst.join("SKILL.md")).unwrap();
+        assert!(merged.contains("<<<<<<<") && merged.contains(">>>>>>>"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn uninstall_skill_removes_only_the_link() {
+        let tmp = TempDir::new().unwrap();
+        let src = tmp.path().join("registry/demo");
+        let dst = tmp.path().join("project/.claude/skills/demo");
+        fs::create_dir_all(&src).unwrap();
+        fs::write(src.join("SKILL.md"), "x").unwrap();
+
+        link_skill(&src, &dst, false).unwrap();
+        assert!(transaction::is_symlink(&dst));
+
+        uninstall_skill(&dst).unwrap();
+        assert!(fs::symlink_metadata(&dst).is_err());
+        assert!(src.join("SKILL.md").is_file());
+
+        install_skill(&src, &dst, false).unwrap();
+        assert!(link_skill(&src, &dst, false).is_err());
+    }
+
+    #[test]
+    fn hook_install_copies_scripts_and_expands_hook_dir() {
+        let tmp = TempDir::new().unwrap();
+        let _env = crate::config::isolate(tmp.path());
+        let hook_dir = tmp.path().join("registry/claude/hooks/fmt");
+        fs::create_dir_all(hook_dir.join("scripts/lib")).unwrap();
+        fs::write(
+            hook_dir.join("HOOK.json"),
+            r#"{"name": "fmt", "description": "d", "hooks": {"PostToolUse": [
+                {"matcher": "Edit", "hooks": [{"type": "command", "command": "${HOOK_DIR}/scripts/run.sh"}]}
+            ]}}"#,
+        )
+        .unwrap();
+        fs::write(hook_dir.join("scripts/run.sh"), "#!/bin/sh\n").unwrap();
+        fs::write(hook_dir.join("scripts/lib/util.sh"), "").unwrap();
+        let settings = tmp.path().join("project/.claude/settings.json");
+
+        install_hook(None, &hook_dir.join("HOOK.json"), &settings, &[], false).unwrap();
+        let installed = tmp.path().join("project/.claude/hooks/fmt");
+        assert!(installed.join("scripts/run.sh").is_file());
+        assert!(installed.join("scripts/lib/util.sh").is_file());
+        let v: Value = serde_json::from_str(&fs::read_to_string(&settings).unwrap()).unwrap();
+        assert_eq!(
+            v["hooks"]["PostToolUse"][0]["hooks"][0]["command"],
+            format!("{}/scripts/run.sh", installed.display())
+        );
+
+        uninstall_hook(None, "fmt", &settings).unwrap();
+        assert!(!installed.exists());
+        assert!(!tmp.path().join("project/.claude/hooks").exists());
+        assert!(!settings.exists());
+    }
+
+    #[test]
+    fn rolled_back_hook_install_leaves_no_snapshot() {
+        let tmp = TempDir::new().unwrap();
+        let _env = crate::config::isolate(tmp.path());
+        let hook_def = tmp.path().join("HOOK.json");
+        fs::write(
+            &hook_def,
+            r#"{"name": "fmt", "description": "d", "hooks": {"Stop": [{"matcher": "", "hooks": []}]}}"#,
+        )
+        .unwrap();
+        let settings = tmp.path().join(".claude/settings.json");
+
+        let mut tx = Transaction::new();
+        tx.track(&settings).unwrap();
+        install_hook(Some(&mut tx), &hook_def, &settings, &[], false).unwrap();
+        assert_eq!(settings_history::list(Some(&settings)).unwrap().len(), 1);
+        tx.rollback().unwrap();
+        assert!(!settings.exists());
+        assert!(settings_history::list(Some(&settings)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn hook_reinstall_keeps_set_parameters() {
+        let tmp = TempDir::new().unwrap();
+        let _env = crate::config::isolate(tmp.path());
+        let hook_def = tmp.path().join("HOOK.json");
+        fs::write(
+            &hook_def,
+            r#"{"name": "say", "description": "d",
+                "parameters": {"message": {"type": "string", "default": "hi"}, "wait": {"type": "integer", "default": 5}},
+                "hooks": {"Stop": [{"hooks": [{"type": "command", "command": "say '{{message}}'", "timeout": "{{wait}}"}]}]}}"#,
+        )
+        .unwrap();
+        let settings = tmp.path().join(".claude/settings.json");
+        let stop = |settings: &Path| -> Value {
+            let v: Value = serde_json::from_str(&fs::read_to_string(settings).unwrap()).unwrap();
+            v["hooks"]["Stop"][0].clone()
+        };
+
+        let set = [("message".to_string(), "bye".to_string())];
+        install_hook(None, &hook_def, &settings, &set, false).unwrap();
+        let group = stop(&settings);
+        assert_eq!(group["hooks"][0]["command"], "say 'bye'");
+        assert_eq!(group["hooks"][0]["timeout"], 5);
+        assert_eq!(group["_registry_params"], serde_json::json!({"message": "bye"}));
+
+        install_hook(None, &hook_def, &settings, &[], false).unwrap();
+        assert_eq!(stop(&settings)["hooks"][0]["command"], "say 'bye'");
+
+        let bad = [("wait".to_string(), "soon".to_string())];
+        assert!(install_hook(None, &hook_def, &settings, &bad, false).is_err());
+    }
+
+    #[test]
+    fn hook_install_keeps_settings_formatting() {
+        let tmp = TempDir::new().unwrap();
+        let _env = crate::config::isolate(tmp.path());
+        let hook_def = tmp.path().join("HOOK.json");
+        fs::write(
+            &hook_def,
+            r#"{"name": "fmt", "description": "d", "hooks": {"Stop": [{"matcher": "", "hooks": []}]}}"#,
+        )
+        .unwrap();
+        let settings = tmp.path().join(".claude/settings.json");
+        fs::create_dir_all(settings.parent().unwrap()).unwrap();
+        let original = "{\n\t// keep me\n\t\"permissions\": { \"allow\": [] },\n\t\"model\": \"opus\",\n}\n";
+        fs::write(&settings, original).unwrap();
+
+        install_hook(None, &hook_def, &settings, &[], true).unwrap();
+        assert_eq!(fs::read_to_string(&settings).unwrap(), original);
+
+        install_hook(None, &hook_def, &settings, &[], false).unwrap();
+        let installed = fs::read_to_string(&settings).unwrap();
+        assert!(installed.starts_with("{\n\t// keep me\n\t\"permissions\": { \"allow\": [] },\n\t\"model\": \"opus\",\n\t\"hooks\": {\n\t\t\"Stop\": [\n\t\t\t{\n\t\t\t\t\"matcher\": \"\","), "{}", installed);
+        install_hook(None, &hook_def, &settings, &[], false).unwrap();
+        assert_eq!(fs::read_to_string(&settings).unwrap(), installed);
+
+        uninstall_hook(None, "fmt", &settings).unwrap();
+        assert_eq!(fs::read_to_string(&settings).unwrap(), original);
+
+        let broken = "{\n\t\"model\": \"opus\"\n\t\"env\": {}\n}\n";
+        fs::write(&settings, broken).unwrap();
+        assert!(install_hook(None, &hook_def, &settings, &[], false).is_err());
+        assert_eq!(fs::read_to_string(&settings).unwrap(), broken);
+    }
+
+    #[test]
+    fn catalog_install_copies_listed_files_and_verifies_hashes() {
+        let tmp = TempDir::new().unwrap();
+        let _env = crate::config::isolate(tmp.path());
+        let root = tmp.path().join("registry");
+        let src = registry::skills_dir(&root).join("demo");
+        fs::create_dir_all(&src).unwrap();
+        fs::write(src.join("SKILL.md"), "skill").unwrap();
+        fs::write(src.join("unlisted.md"), "not in catalog").unwrap();
+        let claude_dir = tmp.path().join("project/.claude");
+        let mut item = CatalogItem {
+            source: root.clone(),
+            signed: root.join("skill-catalog.json"),
+            root,
+            name: "demo".to_string(),
+            files: map(&[("SKILL.md", &lockfile::hash_bytes(b"skill"))]),
+        };
+
+        let mut tx = Transaction::new();
+        sync_catalog_skill(&mut tx, &item, &claude_dir, &InstallOptions::default()).unwrap();
+        tx.commit().unwrap();
+        let dst = claude_dir.join("skills/demo");
+        assert_eq!(fs::read_to_string(dst.join("SKILL.md")).unwrap(), "skill");
+        assert!(!dst.join("unlisted.md").exists());
+
+        fs::remove_dir_all(&claude_dir).unwrap();
+        item.files = map(&[("SKILL.md", "0000")]);
+        let mut tx = Transaction::new();
+        let err = sync_catalog_skill(&mut tx, &item, &claude_dir, &InstallOptions::default());
+        assert!(err.unwrap_err().to_string().contains("expected hash"));
+        tx.rollback().unwrap();
+        assert!(!dst.exists());
+    }
+}
@@ -0,0 +1,762 @@
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::Path;
+
+/// A JSON (or JSONC) document edited in place.
+///
+/// Edits only rewrite the text of the values they touch, so key order,
+/// indentation, comments and trailing commas elsewhere survive.
+pub struct Document {
+    text: String,
+}
+
+struct Node {
+    start: usize,
+    end: usize,
+    kind: Kind,
+}
+
+enum Kind {
+    Object(Vec<Member>),
+    Array(Vec<Node>),
+    Scalar,
+}
+
+struct Member {
+    key: String,
+    key_start: usize,
+    value: Node,
+}
+
+impl Node {
+    /// `(start, end)` of each member (key included) or item.
+    fn children(&self) -> Vec<(usize, usize)> {
+        match &self.kind {
+            Kind::Object(members) => members.iter().map(|m| (m.key_start, m.value.end)).collect(),
+            Kind::Array(items) => items.iter().map(|i| (i.start, i.end)).collect(),
+            Kind::Scalar => Vec::new(),
+        }
+    }
+
+    fn member(&self, key: &str) -> Option<&Member> {
+        match &self.kind {
+            Kind::Object(members) => members.iter().find(|m| m.key == key),
+            _ => None,
+        }
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn fail<T>(&self, what: &str) -> Result<T> {
+        let line = self.text[..self.pos.min(self.text.len())]
+            .matches('\n')
+            .count()
+            + 1;
+        bail!("line {}: {}", line, what)
+    }
+
+    /// Skip whitespace, `//` comments and `/* */` comments.
+    fn skip_ws(&mut self) -> Result<()> {
+        loop {
+            let rest = &self.text[self.pos..];
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+            if trimmed.starts_with("//") {
+                self.pos += trimmed.find('\n').unwrap_or(trimmed.len());
+            } else if trimmed.starts_with("/*") {
+                match trimmed.find("*/") {
+                    Some(end) => self.pos += end + 2,
+                    None => return self.fail("unterminated comment"),
+                }
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<()> {
+        self.skip_ws()?;
+        if self.peek() != Some(byte) {
+            return self.fail(&format!("expected '{}'", byte as char));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let start = self.pos;
+        self.pos += 1;
+        loop {
+            match self.peek() {
+                None => return self.fail("unterminated string"),
+                Some(b'\\') => self.pos += 2,
+                Some(b'"') => break,
+                Some(_) => self.pos += 1,
+            }
+        }
+        self.pos += 1;
+        match serde_json::from_str(&self.text[start..self.pos]) {
+            Ok(s) => Ok(s),
+            Err(e) => self.fail(&format!("invalid string: {}", e)),
+        }
+    }
+
+    fn value(&mut self) -> Result<Node> {
+        self.skip_ws()?;
+        let start = self.pos;
+        let kind = match self.peek() {
+            Some(b'{') => {
+                self.pos += 1;
+                let mut members = Vec::new();
+                loop {
+                    self.skip_ws()?;
+                    match self.peek() {
+                        Some(b'}') => break,
+                        Some(b'"') => {}
+                        _ => return self.fail("expected a key or '}'"),
+                    }
+                    let key_start = self.pos;
+                    let key = self.string()?;
+                    self.expect(b':')?;
+                    let value = self.value()?;
+                    members.push(Member {
+                        key,
+                        key_start,
+                        value,
+                    });
+                    if !self.separator(b'}')? {
+                        break;
+                    }
+                }
+                self.pos += 1;
+                Kind::Object(members)
+            }
+            Some(b'[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                loop {
+                    self.skip_ws()?;
+                    if self.peek() == Some(b']') {
+                        break;
+                    }
+                    items.push(self.value()?);
+                    if !self.separator(b']')? {
+                        break;
+                    }
+                }
+                self.pos += 1;
+                Kind::Array(items)
+            }
+            Some(b'"') => {
+                self.string()?;
+                Kind::Scalar
+            }
+            _ => {
+                let len = self.text[start..]
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || "+-.".contains(c)))
+                    .unwrap_or(self.text.len() - start);
+                if len == 0
+                    || serde_json::from_str::<Value>(&self.text[start..start + len]).is_err()
+                {
+                    return self.fail("expected a value");
+                }
+                self.pos += len;
+                Kind::Scalar
+            }
+        };
+        Ok(Node {
+            start,
+            end: self.pos,
+            kind,
+        })
+    }
+
+    /// After a member or item: consume a comma (true) or stop at `close` (false).
+    fn separator(&mut self, close: u8) -> Result<bool> {
+        self.skip_ws()?;
+        match self.peek() {
+            Some(b',') => {
+                self.pos += 1;
+                Ok(true)
+            }
+            Some(c) if c == close => Ok(false),
+            _ => self.fail(&format!("expected ',' or '{}'", close as char)),
+        }
+    }
+}
+
+impl Document {
+    pub fn parse(text: &str) -> Result<Document> {
+        let doc = Document {
+            text: text.to_string(),
+        };
+        doc.root()?;
+        Ok(doc)
+    }
+
+    /// Read `path`; a missing or empty file is an empty object.
+    pub fn load(path: &Path) -> Result<Document> {
+        let text = match path.exists() {
+            true => fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?,
+            false => String::new(),
+        };
+        if text.trim().is_empty() {
+            return Ok(Document {
+                text: "{}\n".to_string(),
+            });
+        }
+        Document::parse(&text).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    fn root(&self) -> Result<Node> {
+        let mut parser = Parser {
+            text: &self.text,
+            pos: 0,
+        };
+        let root = parser.value()?;
+        parser.skip_ws()?;
+        if parser.pos != self.text.len() {
+            return parser.fail("unexpected content after the document");
+        }
+        Ok(root)
+    }
+
+    /// The whole document as a value (comments dropped).
+    pub fn value(&self) -> Result<Value> {
+        Ok(self.to_value(&self.root()?))
+    }
+
+    fn to_value(&self, node: &Node) -> Value {
+        match &node.kind {
+            Kind::Object(members) => Value::Object(
+                members
+                    .iter()
+                    .map(|m| (m.key.clone(), self.to_value(&m.value)))
+                    .collect::<Map<_, _>>(),
+            ),
+            Kind::Array(items) => Value::Array(items.iter().map(|i| self.to_value(i)).collect()),
+            Kind::Scalar => {
+                serde_json::from_str(&self.text[node.start..node.end]).unwrap_or(Value::Null)
+            }
+        }
+    }
+
+    /// The value at an object `path`, if present.
+    pub fn get(&self, path: &[&str]) -> Result<Option<Value>> {
+        let root = self.root()?;
+        Ok(find(&root, path).map(|n| self.to_value(n)))
+    }
+
+    /// Set the value at `path`, creating missing parent objects.
+    pub fn set(&mut self, path: &[&str], value: &Value) -> Result<()> {
+        let root = self.root()?;
+        let mut node = &root;
+        for (depth, key) in path.iter().enumerate() {
+            if !matches!(node.kind, Kind::Object(_)) {
+                bail!("'{}' is not an object", path[..depth].join("."));
+            }
+            match node.member(key) {
+                Some(member) if depth + 1 == path.len() => {
+                    let indent = line_indent(&self.text, member.key_start).to_string();
+                    let text = self.render(value, &indent);
+                    self.text
+                        .replace_range(member.value.start..member.value.end, &text);
+                    return Ok(());
+                }
+                Some(member) => node = &member.value,
+                None => {
+                    // Wrap the value in the objects that are still missing.
+                    let mut nested = value.clone();
+                    for k in path[depth + 1..].iter().rev() {
+                        nested = Value::Object(Map::from_iter([(k.to_string(), nested)]));
+                    }
+                    let member = Value::Object(Map::from_iter([(key.to_string(), nested)]));
+                    return self.insert(node, &member);
+                }
+            }
+        }
+        bail!("cannot replace the whole document")
+    }
+
+    /// Remove the member at `path`; false if it does not exist.
+    pub fn remove(&mut self, path: &[&str]) -> Result<bool> {
+        let Some((key, parent)) = path.split_last() else {
+            bail!("cannot remove the whole document");
+        };
+        let root = self.root()?;
+        let Some(node) = find(&root, parent) else {
+            return Ok(false);
+        };
+        let Kind::Object(members) = &node.kind else {
+            return Ok(false);
+        };
+        match members.iter().position(|m| m.key == *key) {
+            Some(idx) => {
+                self.remove_child(node, idx);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Append `items` to the array at `path`, creating it if missing.
+    pub fn push_items(&mut self, path: &[&str], items: &[Value]) -> Result<()> {
+        if items.is_empty() {
+            return Ok(());
+        }
+        let root = self.root()?;
+        match find(&root, path) {
+            None => self.set(path, &Value::Array(items.to_vec())),
+            Some(node) if !matches!(node.kind, Kind::Array(_)) => {
+                bail!("'{}' is not an array", path.join("."))
+            }
+            Some(_) => {
+                for item in items {
+                    let root = self.root()?;
+                    let node = find(&root, path).context("array disappeared")?;
+                    self.insert(node, item)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Remove the items of the array at `path` for which `pred` holds.
+    pub fn remove_items(&mut self, path: &[&str], pred: impl Fn(&Value) -> bool) -> Result<usize> {
+        let mut removed = 0;
+        loop {
+            let root = self.root()?;
+            let Some(node) = find(&root, path) else {
+                return Ok(removed);
+            };
+            let Kind::Array(items) = &node.kind else {
+                bail!("'{}' is not an array", path.join("."));
+            };
+            match items.iter().rposition(|i| pred(&self.to_value(i))) {
+                Some(idx) => {
+                    self.remove_child(node, idx);
+                    removed += 1;
+                }
+                None => return Ok(removed),
+            }
+        }
+    }
+
+    /// Serialise `value` pretty-printed with the document's indentation,
+    /// continuation lines indented by `indent`.
+    fn render(&self, value: &Value, indent: &str) -> String {
+        let unit = indent_unit(&self.text);
+        let mut buf = Vec::new();
+        let mut ser = serde_json::Serializer::with_formatter(
+            &mut buf,
+            serde_json::ser::PrettyFormatter::with_indent(unit.as_bytes()),
+        );
+        value
+            .serialize(&mut ser)
+            .expect("serialising a Value cannot fail");
+        String::from_utf8_lossy(&buf).replace('\n', &format!("\n{}", indent))
+    }
+
+    /// Append to `container`: an item, or for objects each member of `value`.
+    fn insert(&mut self, container: &Node, value: &Value) -> Result<()> {
+        let is_object = matches!(container.kind, Kind::Object(_));
+        let entries: Vec<(Option<&str>, &Value)> = match (is_object, value) {
+            (true, Value::Object(map)) => map.iter().map(|(k, v)| (Some(k.as_str()), v)).collect(),
+            (true, _) => bail!("only members can be added to an object"),
+            (false, v) => vec![(None, v)],
+        };
+        let children = container.children();
+        let outer = line_indent(&self.text, container.start).to_string();
+        // An empty container follows the document: compact when the whole
+        // file is one line, except for a fresh `{}` root.
+        let inline = match children.first() {
+            Some(&(start, _)) => !own_line(&self.text, start),
+            None => container.start != self.root()?.start && !self.text.trim().contains('\n'),
+        };
+        let indent = match children.first() {
+            Some(&(start, _)) if !inline => line_indent(&self.text, start).to_string(),
+            _ => format!("{}{}", outer, indent_unit(&self.text)),
+        };
+        let entry = |doc: &Document, (key, v): (Option<&str>, &Value)| {
+            let v = match inline {
+                true => serde_json::to_string(v).expect("serialising a Value cannot fail"),
+                false => doc.render(v, &indent),
+            };
+            match key {
+                Some(k) => format!("{}: {}", Value::from(k), v),
+                None => v,
+            }
+        };
+        let rendered: Vec<String> = entries.into_iter().map(|e| entry(self, e)).collect();
+
+        let Some(&(_, last_end)) = children.last() else {
+            let open = container.start + 1;
+            let close = container.end - 1;
+            if inline {
+                self.text
+                    .replace_range(open..close, &rendered.join(", "));
+                return Ok(());
+            }
+            let inner_end = open + self.text[open..close].trim_end().len();
+            let body: String = rendered
+                .iter()
+                .map(|r| format!("\n{}{}", indent, r))
+                .collect::<Vec<_>>()
+                .join(",");
+            let tail = if inner_end == open {
+                format!("\n{}", outer)
+            } else {
+                self.text[inner_end..close].to_string()
+            };
+            self.text
+                .replace_range(inner_end..close, &format!("{}{}", body, tail));
+            return Ok(());
+        };
+
+        // Keep a trailing comma style: `a, b,` stays `a, b, c,`.
+        let next = next_token(&self.text, last_end);
+        let trailing_comma = self.text.as_bytes().get(next) == Some(&b',');
+        let after_comma = next + 1;
+        if inline {
+            let items = rendered.join(", ");
+            match trailing_comma {
+                true => self.text.insert_str(after_comma, &format!(" {},", items)),
+                false => self.text.insert_str(last_end, &format!(", {}", items)),
+            }
+            return Ok(());
+        }
+        let mut body: String = rendered
+            .iter()
+            .map(|r| format!("\n{}{}", indent, r))
+            .collect::<Vec<_>>()
+            .join(",");
+        if trailing_comma {
+            body.push(',');
+        }
+        // Insert after a comment that ends the last line, then add the comma
+        // right after the last value.
+        let mut pos = if trailing_comma {
+            after_comma
+        } else {
+            last_end
+        };
+        let line_end = self.text[pos..]
+            .find('\n')
+            .map_or(self.text.len(), |i| pos + i);
+        let rest = self.text[pos..line_end].trim();
+        if rest.is_empty() || rest.starts_with("//") {
+            pos = line_end;
+        }
+        self.text.insert_str(pos, &body);
+        if !trailing_comma {
+            self.text.insert(last_end, ',');
+        }
+        Ok(())
+    }
+
+    fn remove_child(&mut self, container: &Node, idx: usize) {
+        let children = container.children();
+        let (start, end) = children[idx];
+        if children.len() == 1 {
+            self.text
+                .replace_range(container.start + 1..container.end - 1, "");
+            return;
+        }
+        let own = own_line(&self.text, start);
+        let next = next_token(&self.text, end);
+        if self.text.as_bytes().get(next) == Some(&b',') {
+            // Drop the child with its comma (and its line, with a `//` comment
+            // ending it, when nothing else is on it).
+            let start = if own {
+                line_start(&self.text, start)
+            } else {
+                start
+            };
+            let rest = &self.text[next + 1..];
+            let skip = match own {
+                true => rest.find('\n').map_or(0, |i| i + 1),
+                false => rest.len() - rest.trim_start_matches(' ').len(),
+            };
+            let tail = rest[..skip].trim();
+            let skip = if tail.is_empty() || (own && tail.starts_with("//")) {
+                skip
+            } else {
+                0
+            };
+            self.text.replace_range(start..next + 1 + skip, "");
+        } else {
+            // The last child: drop its line and the comma after the previous one.
+            let comma = next_token(&self.text, children[idx - 1].1);
+            if !own {
+                self.text.replace_range(comma..end, "");
+                return;
+            }
+            let line_end = self.text[end..]
+                .find('\n')
+                .map_or(self.text.len(), |i| end + i);
+            let end = match self.text[end..line_end].trim_start().starts_with("//") {
+                true => line_end,
+                false => end,
+            };
+            self.text
+                .replace_range(line_start(&self.text, start) - 1..end, "");
+            self.text.remove(comma);
+        }
+    }
+}
+
+fn find<'a>(root: &'a Node, path: &[&str]) -> Option<&'a Node> {
+    path.iter()
+        .try_fold(root, |node, key| node.member(key).map(|m| &m.value))
+}
+
+/// Position of the next character that is not whitespace or a comment.
+fn next_token(text: &str, pos: usize) -> usize {
+    let mut parser = Parser { text, pos };
+    let _ = parser.skip_ws();
+    parser.pos
+}
+
+fn line_start(text: &str, pos: usize) -> usize {
+    text[..pos].rfind('\n').map_or(0, |i| i + 1)
+}
+
+/// True if only whitespace precedes `pos` on its line.
+fn own_line(text: &str, pos: usize) -> bool {
+    text[line_start(text, pos)..pos].trim().is_empty()
+}
+
+/// Leading whitespace of the line containing `pos`.
+fn line_indent(text: &str, pos: usize) -> &str {
+    let line = &text[line_start(text, pos)..];
+    &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+}
+
+/// The indentation step used by the document (two spaces if it has none).
+fn indent_unit(text: &str) -> &str {
+    text.lines()
+        .map(|l| &l[..l.len() - l.trim_start_matches([' ', '\t']).len()])
+        .find(|indent| !indent.is_empty())
+        .unwrap_or("  ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SETTINGS: &str = r#"{
+    // user settings
+    "model": "opus",
+    "hooks": {
+        "Stop": [
+            { "matcher": "", "hooks": [] }, // mine
+            {
+                "_registry_id": "old",
+                "hooks": []
+            }
+        ]
+    },
+    "env": { "A": "1" }
+}
+"#;
+
+    #[test]
+    fn edits_preserve_unrelated_text() {
+        let mut doc = Document::parse(SETTINGS).unwrap();
+        let tagged = |v: &Value| v.get("_registry_id").is_some();
+        assert_eq!(doc.remove_items(&["hooks", "Stop"], tagged).unwrap(), 1);
+        doc.push_items(
+            &["hooks", "Stop"],
+            &[json!({"matcher": "x", "_registry_id": "new"})],
+        )
+        .unwrap();
+        doc.push_items(&["hooks", "PreToolUse"], &[json!({"matcher": "Bash"})])
+            .unwrap();
+        doc.set(&["env", "B"], &json!("2")).unwrap();
+        doc.set(&["model"], &json!("sonnet")).unwrap();
+        assert_eq!(
+            doc.text(),
+            r#"{
+    // user settings
+    "model": "sonnet",
+    "hooks": {
+        "Stop": [
+            { "matcher": "", "hooks": [] }, // mine
+            {
+                "matcher": "x",
+                "_registry_id": "new"
+            }
+        ],
+        "PreToolUse": [
+            {
+                "matcher": "Bash"
+            }
+        ]
+    },
+    "env": { "A": "1", "B": "2" }
+}
+"#
+        );
+        let keys: Vec<String> = doc
+            .value()
+            .unwrap()
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect();
+        assert_eq!(keys, ["model", "hooks", "env"]);
+
+        assert!(doc.remove(&["hooks", "PreToolUse"]).unwrap());
+        assert!(!doc.remove(&["hooks", "Missing"]).unwrap());
+        assert_eq!(doc.remove_items(&["hooks", "Stop"], |_| true).unwrap(), 2);
+        assert!(
+            doc.text().contains("\"Stop\": []\n    },"),
+            "{}",
+            doc.text()
+        );
+    }
+
+    #[test]
+    fn creates_containers_and_keeps_trailing_commas() {
+        let mut doc = Document::load(Path::new("/nonexistent/settings.json")).unwrap();
+        doc.push_items(&["hooks", "Stop"], &[json!({"matcher": ""})])
+            .unwrap();
+        assert_eq!(
+            doc.text(),
+            "{\n  \"hooks\": {\n    \"Stop\": [\n      {\n        \"matcher\": \"\"\n      }\n    ]\n  }\n}\n"
+        );
+
+        let mut doc = Document::parse("{\n  \"a\": [\n    1,\n  ],\n}").unwrap();
+        doc.push_items(&["a"], &[json!(2)]).unwrap();
+        assert_eq!(doc.text(), "{\n  \"a\": [\n    1,\n    2,\n  ],\n}");
+        assert!(Document::parse("{\"a\": }").is_err());
+        assert!(Document::parse("{} x").is_err());
+    }
+
+    #[test]
+    fn comments_and_trailing_commas_are_not_values() {
+        let text = "/* head */ {\n  \"a\" /* k */ : [1, /* x */ 2,], // end\n  \"b\": { \"c\": true, },\n}\n// tail\n";
+        let mut doc = Document::parse(text).unwrap();
+        assert_eq!(doc.value().unwrap(), json!({"a": [1, 2], "b": {"c": true}}));
+        doc.set(&["b", "c"], &json!(false)).unwrap();
+        doc.push_items(&["a"], &[json!(3)]).unwrap();
+        assert_eq!(
+            doc.text(),
+            "/* head */ {\n  \"a\" /* k */ : [1, /* x */ 2, 3,], // end\n  \"b\": { \"c\": false, },\n}\n// tail\n"
+        );
+        assert!(Document::parse("{ /* open").is_err());
+        assert!(Document::parse("{} /* open").is_err());
+    }
+
+    #[test]
+    fn strings_keep_escapes_and_comment_markers() {
+        let text = r#"{
+  "url": "https://example.com//path", // a comment
+  "say": "a \"quoted\" /* not a comment */ \\",
+  "caf\u00e9": "\u00e9\ud83d\ude00",
+  "list": ["//", "/*"]
+}"#;
+        let mut doc = Document::parse(text).unwrap();
+        assert_eq!(
+            doc.get(&["url"]).unwrap(),
+            Some(json!("https://example.com//path"))
+        );
+        assert_eq!(
+            doc.get(&["say"]).unwrap(),
+            Some(json!("a \"quoted\" /* not a comment */ \\"))
+        );
+        assert_eq!(doc.get(&["café"]).unwrap(), Some(json!("é😀")));
+        assert_eq!(doc.get(&["list"]).unwrap(), Some(json!(["//", "/*"])));
+
+        doc.set(&["café"], &json!("\"\n")).unwrap();
+        assert!(doc.text().contains(r#""caf\u00e9": "\"\n","#), "{}", doc.text());
+        assert_eq!(doc.remove_items(&["list"], |v| v == "//").unwrap(), 1);
+        assert!(doc.remove(&["url"]).unwrap());
+        doc.set(&["extra"], &json!(1)).unwrap();
+        let text = doc.text().replace("\"extra\": 1", "\"extra\": 1 // gone too");
+        let mut doc = Document::parse(&text).unwrap();
+        assert!(doc.remove(&["extra"]).unwrap());
+        assert_eq!(
+            doc.text(),
+            r#"{
+  "say": "a \"quoted\" /* not a comment */ \\",
+  "caf\u00e9": "\"\n",
+  "list": ["/*"]
+}"#
+        );
+    }
+
+    #[test]
+    fn edits_empty_and_single_item_arrays() {
+        let mut doc = Document::parse("{\n  \"a\": [],\n  \"b\": [1],\n  \"c\": [\n    1\n  ]\n}\n").unwrap();
+        assert_eq!(doc.remove_items(&["a"], |_| true).unwrap(), 0);
+        assert_eq!(doc.remove_items(&["missing"], |_| true).unwrap(), 0);
+        assert_eq!(doc.remove_items(&["b"], |_| true).unwrap(), 1);
+        assert_eq!(doc.remove_items(&["c"], |_| true).unwrap(), 1);
+        assert_eq!(doc.text(), "{\n  \"a\": [],\n  \"b\": [],\n  \"c\": []\n}\n");
+
+        doc.push_items(&["a"], &[json!(1)]).unwrap();
+        doc.push_items(&["b"], &[]).unwrap();
+        assert_eq!(doc.text(), "{\n  \"a\": [\n    1\n  ],\n  \"b\": [],\n  \"c\": []\n}\n");
+        doc.push_items(&["a"], &[json!(2), json!(3)]).unwrap();
+        assert_eq!(doc.value().unwrap()["a"], json!([1, 2, 3]));
+
+        let mut doc = Document::parse("{\"a\": [1], \"b\": []}").unwrap();
+        doc.push_items(&["a"], &[json!(2)]).unwrap();
+        doc.push_items(&["b"], &[json!("x")]).unwrap();
+        assert_eq!(doc.text(), "{\"a\": [1, 2], \"b\": [\"x\"]}");
+        assert!(doc.push_items(&["a", "0"], &[json!(1)]).is_err());
+        assert!(doc.remove_items(&["b", "x"], |_| true).is_ok());
+    }
+
+    #[test]
+    fn malformed_input_is_an_error_and_the_file_is_left_alone() {
+        for text in [
+            "",
+            "{",
+            "{\"a\": 1,, }",
+            "{\"a\" 1}",
+            "{\"a\": \"open}",
+            "{\"a\": \"\\x\"}",
+            "{'a': 1}",
+            "{\"a\": tru}",
+            "[1 2]",
+            "{\"a\": 1}}",
+        ] {
+            assert!(Document::parse(text).is_err(), "parsed {:?}", text);
+        }
+        let err = Document::parse("{\n  \"a\": 1\n  \"b\": 2\n}").err().unwrap();
+        assert_eq!(err.to_string(), "line 3: expected ',' or '}'");
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("settings.json");
+        fs::write(&path, "{\"hooks\": {\n").unwrap();
+        let err = Document::load(&path).err().unwrap();
+        assert!(err.to_string().starts_with("Failed to parse"), "{}", err);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"hooks\": {\n");
+
+        let mut doc = Document::parse("{\"a\": 1}").unwrap();
+        assert!(doc.set(&["a", "b"], &json!(2)).is_err());
+        assert!(doc.push_items(&["a"], &[json!(2)]).is_err());
+        assert_eq!(doc.text(), "{\"a\": 1}");
+    }
+}
+
@@ -0,0 +1,495 @@
+use anyhow::Result;
+use serde_json::Value;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::frontmatter::{self, AgentMeta, SkillMeta};
+use crate::hook_params::{self, Parameters};
+use crate::installer::HOOK_DIR_PLACEHOLDER;
+use crate::lockfile::ItemKind;
+use crate::mcp;
+use crate::permissions::Rules;
+use crate::registry::{self, Registry};
+
+/// Hook events Claude Code dispatches.
+pub const HOOK_EVENTS: &[&str] = &[
+    "PreToolUse",
+    "PermissionRequest",
+    "PostToolUse",
+    "PostToolUseFailure",
+    "Notification",
+    "UserPromptSubmit",
+    "Stop",
+    "SubagentStart",
+    "SubagentStop",
+    "PreCompact",
+    "SessionStart",
+    "SessionEnd",
+];
+
+/// A problem found in registry content.
+pub struct Diagnostic {
+    pub path: PathBuf,
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.path.display(), self.line, self.message)
+    }
+}
+
+/// Collects diagnostics, reporting paths relative to the registry root.
+struct Linter<'a> {
+    root: &'a Path,
+    found: Vec<Diagnostic>,
+}
+
+impl Linter<'_> {
+    fn report(&mut self, path: &Path, line: usize, message: String) {
+        let path = path.strip_prefix(self.root).unwrap_or(path).to_path_buf();
+        self.found.push(Diagnostic { path, line, message });
+    }
+}
+
+/// 1-based line of the first occurrence of `needle`, or 1 if absent.
+pub fn line_of(content: &str, needle: &str) -> usize {
+    content
+        .find(needle)
+        .map_or(1, |pos| content[..pos].matches('\n').count() + 1)
+}
+
+/// 1-based line of a top-level `key:` in markdown frontmatter, or 1 if absent.
+fn key_line(content: &str, key: &str) -> usize {
+    let prefix = format!("{}:", key);
+    content
+        .lines()
+        .position(|l| l.starts_with(&prefix))
+        .map_or(1, |i| i + 1)
+}
+
+/// Check every skill, agent, hook, MCP definition and profile under `root`.
+///
+/// Profiles may reference items in other sources (`source/item`); those
+/// references are checked against `reg` when given.
+pub fn lint(root: &Path, reg: Option<&Registry>) -> Result<Vec<Diagnostic>> {
+    let mut l = Linter {
+        root,
+        found: Vec::new(),
+    };
+    lint_skills(&mut l)?;
+    lint_agents(&mut l)?;
+    lint_hooks(&mut l)?;
+    lint_mcp(&mut l)?;
+    lint_profiles(&mut l, reg)?;
+    Ok(l.found)
+}
+
+/// Subdirectories of `dir`, sorted; empty if `dir` does not exist.
+fn subdirs(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        return Ok(vec![]);
+    }
+    let mut dirs = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            dirs.push(path);
+        }
+    }
+    dirs.sort();
+    Ok(dirs)
+}
+
+/// Shared checks for markdown items: frontmatter present and well-formed,
+/// `name` matching the directory or file stem, non-empty description.
+fn lint_markdown(
+    l: &mut Linter,
+    path: &Path,
+    expected: &str,
+    meta: impl FnOnce(&str) -> Result<Option<(Option<String>, String)>, frontmatter::FrontmatterError>,
+) -> Result<()> {
+    let content = std::fs::read_to_string(path)?;
+    let (name, description) = match meta(&content) {
+        Ok(Some(fields)) => fields,
+        Ok(None) => {
+            l.report(path, 1, "missing frontmatter (expected a leading '---' block)".into());
+            return Ok(());
+        }
+        Err(e) => {
+            l.report(path, e.line, format!("invalid frontmatter: {}", e.message));
+            return Ok(());
+        }
+    };
+    match name {
+        None => l.report(path, line_of(&content, "---"), "frontmatter has no 'name'".into()),
+        Some(name) if name != expected => l.report(
+            path,
+            key_line(&content, "name"),
+            format!("name '{}' does not match '{}'", name, expected),
+        ),
+        Some(_) => {}
+    }
+    if description.is_empty() {
+        let line = match key_line(&content, "description") {
+            1 => line_of(&content, "---"),
+            n => n,
+        };
+        l.report(path, line, "description is empty".into());
+    }
+    Ok(())
+}
+
+fn lint_skills(l: &mut Linter) -> Result<()> {
+    for dir in subdirs(&registry::skills_dir(l.root))? {
+        let skill_md = dir.join("SKILL.md");
+        if !skill_md.is_file() {
+            l.report(&dir, 1, "skill directory has no SKILL.md".into());
+            continue;
+        }
+        let expected = registry::dir_name(&dir);
+        lint_markdown(l, &skill_md, &expected, |content| {
+            frontmatter::parse::<SkillMeta>(content).map(|m| m.map(|m| (m.name, m.description)))
+        })?;
+    }
+    Ok(())
+}
+
+fn lint_agents(l: &mut Linter) -> Result<()> {
+    for file in registry::list_agents(l.root)? {
+        let expected = registry::file_stem(&file);
+        lint_markdown(l, &file, &expected, |content| {
+            frontmatter::parse::<AgentMeta>(content).map(|m| m.map(|m| (m.name, m.description)))
+        })?;
+    }
+    Ok(())
+}
+
+fn lint_hooks(l: &mut Linter) -> Result<()> {
+    for dir in subdirs(&registry::hooks_dir(l.root))? {
+        let path = dir.join("HOOK.json");
+        if !path.is_file() {
+            l.report(&dir, 1, "hook directory has no HOOK.json".into());
+            continue;
+        }
+        let content = std::fs::read_to_string(&path)?;
+        let def: Value = match serde_json::from_str(&content) {
+            Ok(v) => v,
+            Err(e) => {
+                l.report(&path, e.line(), format!("invalid JSON: {}", e));
+                continue;
+            }
+        };
+        let expected = registry::dir_name(&dir);
+        match def.get("name").and_then(Value::as_str) {
+            None => l.report(&path, 1, "missing string field 'name'".into()),
+            Some(name) if name != expected => l.report(
+                &path,
+                line_of(&content, "\"name\""),
+                format!("name '{}' does not match '{}'", name, expected),
+            ),
+            Some(_) => {}
+        }
+        if def.get("description").and_then(Value::as_str).unwrap_or("").trim().is_empty() {
+            l.report(&path, line_of(&content, "\"description\""), "description is empty".into());
+        }
+        let params = match def.get("parameters").cloned().map(serde_json::from_value) {
+            None => Some(Parameters::new()),
+            Some(Ok(params)) => Some(params),
+            Some(Err(e)) => {
+                let line = line_of(&content, "\"parameters\"");
+                l.report(&path, line, format!("invalid parameters: {}", e));
+                None
+            }
+        };
+        for (name, param) in params.iter().flatten() {
+            if param.default.as_ref().is_some_and(|d| !param.kind.accepts(d)) {
+                l.report(
+                    &path,
+                    line_of(&content, &format!("\"{}\"", name)),
+                    format!("default of parameter '{}' does not match its type '{}'", name, param.kind),
+                );
+            }
+        }
+        if let Some(Err(e)) = def.get("permissions").cloned().map(serde_json::from_value::<Rules>) {
+            let line = line_of(&content, "\"permissions\"");
+            l.report(&path, line, format!("invalid permissions: {}", e));
+        }
+        let Some(events) = def.get("hooks").and_then(Value::as_object) else {
+            l.report(&path, line_of(&content, "\"hooks\""), "'hooks' must be an object of events".into());
+            continue;
+        };
+        if let Some(params) = &params {
+            for name in hook_params::placeholders(&def["hooks"]) {
+                if !params.contains_key(&name) {
+                    let line = line_of(&content, &format!("{{{{{}}}}}", name));
+                    l.report(&path, line, format!("hooks refer to undeclared parameter '{}'", name));
+                }
+            }
+        }
+        for (event, groups) in events {
+            let line = line_of(&content, &format!("\"{}\"", event));
+            if !HOOK_EVENTS.contains(&event.as_str()) {
+                l.report(&path, line, format!("unknown hook event '{}'", event));
+            }
+            for problem in hook_group_problems(groups) {
+                l.report(&path, line, format!("hooks.{}: {}", event, problem));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn lint_mcp(l: &mut Linter) -> Result<()> {
+    for dir in subdirs(&registry::mcp_dir(l.root))? {
+        let path = dir.join("MCP.json");
+        if !path.is_file() {
+            l.report(&dir, 1, "MCP directory has no MCP.json".into());
+            continue;
+        }
+        let content = std::fs::read_to_string(&path)?;
+        let def: Value = match serde_json::from_str(&content) {
+            Ok(v) => v,
+            Err(e) => {
+                l.report(&path, e.line(), format!("invalid JSON: {}", e));
+                continue;
+            }
+        };
+        let expected = registry::dir_name(&dir);
+        match def.get("name").and_then(Value::as_str) {
+            None => l.report(&path, 1, "missing string field 'name'".into()),
+            Some(name) if name != expected => l.report(
+                &path,
+                line_of(&content, "\"name\""),
+                format!("name '{}' does not match '{}'", name, expected),
+            ),
+            Some(_) => {}
+        }
+        if def.get("description").and_then(Value::as_str).unwrap_or("").trim().is_empty() {
+            l.report(&path, line_of(&content, "\"description\""), "description is empty".into());
+        }
+        let vars = def.get("variables").cloned().map(serde_json::from_value::<mcp::Variables>);
+        let vars = match vars {
+            None => Some(mcp::Variables::new()),
+            Some(Ok(vars)) => Some(vars),
+            Some(Err(e)) => {
+                let line = line_of(&content, "\"variables\"");
+                l.report(&path, line, format!("invalid variables: {}", e));
+                None
+            }
+        };
+        let servers = match def.get("mcpServers").and_then(Value::as_object) {
+            Some(servers) if !servers.is_empty() => servers,
+            _ => {
+                let line = line_of(&content, "\"mcpServers\"");
+                l.report(&path, line, "'mcpServers' must be a non-empty object of servers".into());
+                continue;
+            }
+        };
+        for (name, server) in servers {
+            let line = line_of(&content, &format!("\"{}\"", name));
+            let field = |k| server.get(k).and_then(Value::as_str);
+            let problem = match (field("type").unwrap_or("stdio"), field("command"), field("url")) {
+                ("stdio", None, _) => Some("stdio server has no command"),
+                ("http" | "sse", _, None) => Some("remote server has no url"),
+                ("stdio" | "http" | "sse", _, _) => None,
+                _ => Some("type must be stdio, http or sse"),
+            };
+            if let Some(problem) = problem {
+                l.report(&path, line, format!("mcpServers.{}: {}", name, problem));
+            }
+            if server.get(mcp::OWNER_KEY).is_some() {
+                let key = mcp::OWNER_KEY;
+                l.report(&path, line, format!("mcpServers.{}: {} is set by the installer", name, key));
+            }
+        }
+        if let Some(vars) = vars {
+            let used = mcp::placeholders(&def["mcpServers"]);
+            for name in vars.keys().filter(|v| !used.contains(*v)) {
+                let line = line_of(&content, &format!("\"{}\"", name));
+                l.report(&path, line, format!("variable '{}' is not used by any server", name));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Structural problems in one event's array of matcher groups.
+fn hook_group_problems(groups: &Value) -> Vec<String> {
+    let Some(groups) = groups.as_array() else {
+        return vec!["must be an array of matcher groups".into()];
+    };
+    let mut problems = Vec::new();
+    for (i, group) in groups.iter().enumerate() {
+        if group.get("matcher").is_some_and(|m| !m.is_string()) {
+            problems.push(format!("[{}].matcher must be a string", i));
+        }
+        let Some(hooks) = group.get("hooks").and_then(Value::as_array) else {
+            problems.push(format!("[{}].hooks must be an array", i));
+            continue;
+        };
+        for (j, hook) in hooks.iter().enumerate() {
+            // A `{{param}}` timeout is a string here; its value is checked on install.
+            let timeout = hook.get("timeout").and_then(Value::as_f64);
+            if timeout.is_some_and(|t| t <= 0.0) {
+                problems.push(format!(
+                    "[{}].hooks[{}] timeout must be a positive number of seconds",
+                    i, j
+                ));
+            }
+            match hook.get("type").and_then(Value::as_str) {
+                Some("command") => {
+                    let command = hook.get("command").and_then(Value::as_str).unwrap_or("");
+                    if command.trim().is_empty() {
+                        problems.push(format!("[{}].hooks[{}] has no command", i, j));
+                    } else if command.contains("scripts/") && !command.contains(HOOK_DIR_PLACEHOLDER) {
+                        problems.push(format!(
+                            "[{}].hooks[{}] refers to scripts/ without {}",
+                            i, j, HOOK_DIR_PLACEHOLDER
+                        ));
+                    }
+                }
+                Some("prompt") => {}
+                other => problems.push(format!(
+                    "[{}].hooks[{}] has unsupported type {}",
+                    i,
+                    j,
+                    other.map_or("(none)".to_string(), |t| format!("'{}'", t))
+                )),
+            }
+        }
+    }
+    problems
+}
+
+fn lint_profiles(l: &mut Linter, reg: Option<&Registry>) -> Result<()> {
+    for path in registry::list_profiles(l.root)? {
+        let content = std::fs::read_to_string(&path)?;
+        let profile: registry::Profile = match serde_json::from_str(&content) {
+            Ok(p) => p,
+            Err(e) => {
+                l.report(&path, e.line(), format!("invalid profile: {}", e));
+                continue;
+            }
+        };
+        let expected = registry::file_stem(&path);
+        if profile.name != expected {
+            l.report(
+                &path,
+                line_of(&content, "\"name\""),
+                format!("name '{}' does not match '{}'", profile.name, expected),
+            );
+        }
+        if profile.description.trim().is_empty() {
+            l.report(&path, line_of(&content, "\"description\""), "description is empty".into());
+        }
+        let members = profile
+            .skills
+            .iter()
+            .map(|n| (ItemKind::Skill, n))
+            .chain(profile.agents.iter().map(|n| (ItemKind::Agent, n)));
+        for (kind, name) in members {
+            let known = if name.contains('/') {
+                match reg {
+                    Some(reg) => reg.resolve(kind, name).is_ok(),
+                    None => true,
+                }
+            } else {
+                registry::item_path(l.root, kind, name).exists()
+            };
+            if !known {
+                l.report(
+                    &path,
+                    line_of(&content, &format!("\"{}\"", name)),
+                    format!("unknown {} '{}'", kind, name),
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn messages(root: &Path) -> Vec<String> {
+        lint(root, None).unwrap().iter().map(|d| d.to_string()).collect()
+    }
+
+    #[test]
+    fn clean_registry_has_no_diagnostics() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        let skill = root.join("claude/skills/review");
+        fs::create_dir_all(&skill).unwrap();
+        fs::write(skill.join("SKILL.md"), "---\nname: review\ndescription: Reviews code\n---\n").unwrap();
+        fs::create_dir_all(root.join("profiles")).unwrap();
+        fs::write(
+            root.join("profiles/dev.json"),
+            r#"{"name": "dev", "description": "Dev set", "skills": ["review"]}"#,
+        )
+        .unwrap();
+        assert!(messages(root).is_empty(), "{:?}", messages(root));
+    }
+
+    #[test]
+    fn reports_problems_with_file_and_line() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        for (dir, body) in [
+            ("plain", "# No frontmatter\n"),
+            ("renamed", "---\nname: other\ndescription: ''\n---\n"),
+        ] {
+            let skill = root.join("claude/skills").join(dir);
+            fs::create_dir_all(&skill).unwrap();
+            fs::write(skill.join("SKILL.md"), body).unwrap();
+        }
+        let hook = root.join("claude/hooks/notify");
+        fs::create_dir_all(&hook).unwrap();
+        fs::write(
+            hook.join("HOOK.json"),
+            "{\n  \"name\": \"notify\",\n  \"description\": \"n\", \"permissions\": {\"ask\": []},\n  \"parameters\": {\"level\": {\"type\": \"integer\", \"default\": \"high\"}},\n  \"hooks\": {\n    \"OnStop\": [{\"hooks\": [{\"type\": \"command\"}, {\"type\": \"command\", \"command\": \"scripts/a.sh {{who}}\"}, {\"type\": \"command\", \"command\": \"true\", \"timeout\": 0}]}]\n  }\n}\n",
+        )
+        .unwrap();
+        let mcp = root.join("claude/mcp/db");
+        fs::create_dir_all(&mcp).unwrap();
+        fs::write(
+            mcp.join("MCP.json"),
+            "{\n  \"name\": \"db\",\n  \"description\": \"d\",\n  \"variables\": {\"DB_URL\": {}},\n  \"mcpServers\": {\n    \"postgres\": {\"args\": [\"${PORT}\"]},\n    \"docs\": {\"type\": \"http\"}\n  }\n}\n",
+        )
+        .unwrap();
+        fs::create_dir_all(root.join("profiles")).unwrap();
+        fs::write(
+            root.join("profiles/web.json"),
+            "{\n  \"name\": \"dev\",\n  \"description\": \"d\",\n  \"skills\": [\"missing\"]\n}\n",
+        )
+        .unwrap();
+
+        let found = messages(root);
+        let expected = [
+            "claude/skills/plain/SKILL.md:1: missing frontmatter",
+            "claude/skills/renamed/SKILL.md:2: name 'other' does not match 'renamed'",
+            "claude/skills/renamed/SKILL.md:3: description is empty",
+            "claude/hooks/notify/HOOK.json:3: invalid permissions: unknown field `ask`",
+            "claude/hooks/notify/HOOK.json:4: default of parameter 'level' does not match its type 'integer'",
+            "claude/hooks/notify/HOOK.json:6: hooks refer to undeclared parameter 'who'",
+            "claude/hooks/notify/HOOK.json:6: unknown hook event 'OnStop'",
+            "claude/hooks/notify/HOOK.json:6: hooks.OnStop: [0].hooks[0] has no command",
+            "claude/hooks/notify/HOOK.json:6: hooks.OnStop: [0].hooks[1] refers to scripts/ without ${HOOK_DIR}",
+            "claude/hooks/notify/HOOK.json:6: hooks.OnStop: [0].hooks[2] timeout must be a positive number of seconds",
+            "claude/mcp/db/MCP.json:4: variable 'DB_URL' is not used by any server",
+            "claude/mcp/db/MCP.json:6: mcpServers.postgres: stdio server has no command",
+            "claude/mcp/db/MCP.json:7: mcpServers.docs: remote server has no url",
+            "profiles/web.json:2: name 'dev' does not match 'web'",
+            "profiles/web.json:4: unknown skill 'missing'",
+        ];
+        for e in expected {
+            assert!(found.iter().any(|f| f.starts_with(e)), "missing {:?} in {:#?}", e, found);
+        }
+        assert_eq!(found.len(), expected.len(), "{:#?}", found);
+    }
+}
@@ -0,0 +1,436 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::git;
+
+/// File name of the lockfile, stored inside the `.claude` directory.
+pub const LOCK_FILE: &str = "registry-lock.json";
+/// Settings file hooks are installed into unless recorded otherwise.
+pub const SETTINGS_FILE: &str = "settings.json";
+
+const LOCK_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ItemKind {
+    Skill,
+    Agent,
+    Hook,
+    Mcp,
+}
+
+impl fmt::Display for ItemKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            ItemKind::Skill => "skill",
+            ItemKind::Agent => "agent",
+            ItemKind::Hook => "hook",
+            ItemKind::Mcp => "mcp",
+        };
+        f.write_str(s)
+    }
+}
+
+/// How an item was placed in the target.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum InstallMode {
+    /// Files copied from the registry.
+    #[default]
+    Copy,
+    /// Symlink to the registry source.
+    Link,
+}
+
+/// One item installed by claude-registry.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LockEntry {
+    pub kind: ItemKind,
+    pub name: String,
+    /// Registry root the item was installed from.
+    pub source: String,
+    /// Commit checked out in `source` at install time, when it is a git checkout.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    #[serde(default)]
+    pub mode: InstallMode,
+    /// Relative file path → sha256 hex digest.
+    pub files: BTreeMap<String, String>,
+    pub installed_at: String,
+    pub cli_version: String,
+    /// Settings file a hook lives in, when it is not `settings.json`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settings: Option<String>,
+}
+
+impl LockEntry {
+    /// File name of the settings file next to the lockfile this hook is in.
+    pub fn settings_file(&self) -> &str {
+        self.settings.as_deref().unwrap_or(SETTINGS_FILE)
+    }
+}
+
+/// Contents of `.claude/registry-lock.json`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Lockfile {
+    pub version: u32,
+    #[serde(default)]
+    pub items: Vec<LockEntry>,
+}
+
+impl Default for Lockfile {
+    fn default() -> Self {
+        Lockfile {
+            version: LOCK_VERSION,
+            items: Vec::new(),
+        }
+    }
+}
+
+/// Return path to the lockfile: <claude_dir>/registry-lock.json
+pub fn lock_path(claude_dir: &Path) -> PathBuf {
+    claude_dir.join(LOCK_FILE)
+}
+
+/// Return path to the installed-content object store: <claude_dir>/.registry/objects
+///
+/// A pristine copy of every installed file is kept here, keyed by hash, so that
+/// later updates can three-way merge against the version that was installed.
+pub fn objects_dir(claude_dir: &Path) -> PathBuf {
+    claude_dir.join(".registry").join("objects")
+}
+
+fn object_path(claude_dir: &Path, hash: &str) -> PathBuf {
+    let (prefix, rest) = hash.split_at(2.min(hash.len()));
+    objects_dir(claude_dir).join(prefix).join(rest)
+}
+
+impl Lockfile {
+    /// Load the lockfile from `claude_dir`; return an empty lockfile if it does not exist.
+    pub fn load(claude_dir: &Path) -> Result<Self> {
+        let path = lock_path(claude_dir);
+        if !path.exists() {
+            return Ok(Lockfile::default());
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let lock: Lockfile = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        if lock.version > LOCK_VERSION {
+            bail!(
+                "{} has version {}, but this CLI only understands version {}",
+                path.display(),
+                lock.version,
+                LOCK_VERSION
+            );
+        }
+        Ok(lock)
+    }
+
+    /// Write the lockfile to `claude_dir` (pretty-printed, items sorted by kind and name).
+    pub fn save(&mut self, claude_dir: &Path) -> Result<()> {
+        fs::create_dir_all(claude_dir)
+            .with_context(|| format!("Failed to create {}", claude_dir.display()))?;
+        self.items
+            .sort_by(|a, b| (a.kind as u8, &a.name).cmp(&(b.kind as u8, &b.name)));
+        let path = lock_path(claude_dir);
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(&path, format!("{}\n", content))
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(())
+    }
+
+    pub fn get(&self, kind: ItemKind, name: &str) -> Option<&LockEntry> {
+        self.items.iter().find(|e| e.kind == kind && e.name == name)
+    }
+
+    /// Insert or replace the entry with the same kind and name.
+    pub fn upsert(&mut self, entry: LockEntry) {
+        self.remove(entry.kind, &entry.name);
+        self.items.push(entry);
+    }
+
+    pub fn remove(&mut self, kind: ItemKind, name: &str) -> Option<LockEntry> {
+        let pos = self
+            .items
+            .iter()
+            .position(|e| e.kind == kind && e.name == name)?;
+        Some(self.items.remove(pos))
+    }
+
+    pub fn entries(&self, kind: ItemKind) -> impl Iterator<Item = &LockEntry> {
+        self.items.iter().filter(move |e| e.kind == kind)
+    }
+}
+
+/// Compute the sha256 hex digest of a file.
+pub fn hash_file(path: &Path) -> Result<String> {
+    let bytes = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(hash_bytes(&bytes))
+}
+
+pub fn hash_bytes(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Hash every file below `dir` (excluding .gitkeep), keyed by `/`-separated relative path.
+pub fn hash_tree(dir: &Path) -> Result<BTreeMap<String, String>> {
+    let mut files = BTreeMap::new();
+    for entry in WalkDir::new(dir).follow_links(true) {
+        let entry = entry?;
+        if !entry.file_type().is_file() || entry.file_name() == ".gitkeep" {
+            continue;
+        }
+        let rel = entry.path().strip_prefix(dir)?;
+        let key = rel
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        files.insert(key, hash_file(entry.path())?);
+    }
+    Ok(files)
+}
+
+/// Hash a single-file item (agent, HOOK.json), keyed by its file name.
+pub fn hash_single(path: &Path) -> Result<BTreeMap<String, String>> {
+    let name = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    Ok(BTreeMap::from([(name, hash_file(path)?)]))
+}
+
+/// Hash a hook: its HOOK.json plus every file under `scripts/`, keyed relative to the hook directory.
+pub fn hash_hook(dir: &Path) -> Result<BTreeMap<String, String>> {
+    let mut files = hash_single(&dir.join("HOOK.json"))?;
+    let scripts = dir.join("scripts");
+    if scripts.is_dir() {
+        for (rel, hash) in hash_tree(&scripts)? {
+            files.insert(format!("scripts/{}", rel), hash);
+        }
+    }
+    Ok(files)
+}
+
+/// Hash a whole item from its per-file hashes, so one digest identifies its content.
+pub fn content_hash(files: &BTreeMap<String, String>) -> String {
+    let mut listing = String::new();
+    for (path, hash) in files {
+        listing.push_str(&format!("{}  {}\n", hash, path));
+    }
+    hash_bytes(listing.as_bytes())
+}
+
+/// Read the installed version of a file back from the object store.
+pub fn load_object(claude_dir: &Path, hash: &str) -> Option<Vec<u8>> {
+    fs::read(object_path(claude_dir, hash)).ok()
+}
+
+/// Copy `files` (relative to `content_dir`) into the object store.
+fn store_objects(
+    claude_dir: &Path,
+    content_dir: &Path,
+    files: &BTreeMap<String, String>,
+) -> Result<()> {
+    for (rel, hash) in files {
+        let obj = object_path(claude_dir, hash);
+        if obj.exists() {
+            continue;
+        }
+        if let Some(parent) = obj.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        fs::copy(content_dir.join(rel), &obj)
+            .with_context(|| format!("Failed to store {}", obj.display()))?;
+    }
+    Ok(())
+}
+
+/// Delete objects no longer referenced by any lock entry.
+fn prune_objects(claude_dir: &Path, lock: &Lockfile) -> Result<()> {
+    let dir = objects_dir(claude_dir);
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    let referenced: Vec<PathBuf> = lock
+        .items
+        .iter()
+        .flat_map(|e| e.files.values())
+        .map(|h| object_path(claude_dir, h))
+        .collect();
+    for entry in WalkDir::new(&dir).min_depth(2) {
+        let entry = entry?;
+        if entry.file_type().is_file() && !referenced.iter().any(|p| p == entry.path()) {
+            fs::remove_file(entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+/// Record an installed item in `<claude_dir>/registry-lock.json`.
+///
+/// `files` are relative to `content_dir`. For copies, their contents are kept
+/// in the object store as the base for later three-way merges. If `source` is
+/// a git checkout (a git-backed source), its current commit is recorded too.
+pub fn record(
+    claude_dir: &Path,
+    kind: ItemKind,
+    name: &str,
+    source: &Path,
+    mode: InstallMode,
+    content_dir: &Path,
+    files: BTreeMap<String, String>,
+) -> Result<()> {
+    if mode == InstallMode::Copy {
+        store_objects(claude_dir, content_dir, &files)?;
+    }
+    let mut lock = Lockfile::load(claude_dir)?;
+    // Updates re-record an item; it stays in the settings file it was installed into.
+    let settings = lock.get(kind, name).and_then(|e| e.settings.clone());
+    lock.upsert(LockEntry {
+        kind,
+        name: name.to_string(),
+        source: source.display().to_string(),
+        commit: git::head_commit(source),
+        mode,
+        files,
+        installed_at: chrono::Utc::now().to_rfc3339(),
+        cli_version: env!("CARGO_PKG_VERSION").to_string(),
+        settings,
+    });
+    lock.save(claude_dir)?;
+    prune_objects(claude_dir, &lock)
+}
+
+/// Describe how `path` got into the target, for `list` output.
+pub fn describe_installed(entry: Option<&LockEntry>, path: &Path) -> String {
+    let link = fs::read_link(path).ok();
+    let present = path.exists();
+    match (entry, link, present) {
+        (Some(e), None, true) => match &e.commit {
+            Some(c) => format!("copied from {}@{} ({})", e.source, &c[..c.len().min(12)], e.installed_at),
+            None => format!("copied from {} ({})", e.source, e.installed_at),
+        },
+        (Some(_), Some(l), true) => format!("linked -> {}", l.display()),
+        (Some(_), Some(l), false) => format!("broken link -> {}", l.display()),
+        (Some(_), None, false) => "missing (recorded in registry-lock.json)".to_string(),
+        (None, Some(l), _) => format!("unmanaged (link -> {})", l.display()),
+        (None, None, _) => "unmanaged".to_string(),
+    }
+}
+
+/// Record which settings file next to the lockfile hook `name` was installed into.
+pub fn set_hook_settings(claude_dir: &Path, name: &str, file: &str) -> Result<()> {
+    let mut lock = Lockfile::load(claude_dir)?;
+    let entry = lock
+        .items
+        .iter_mut()
+        .find(|e| e.kind == ItemKind::Hook && e.name == name);
+    let Some(entry) = entry else {
+        bail!("hook '{}' is not recorded in {}", name, LOCK_FILE);
+    };
+    entry.settings = (file != SETTINGS_FILE).then(|| file.to_string());
+    lock.save(claude_dir)
+}
+
+/// The settings file hook `name` is recorded in (`settings.json` if it is not recorded).
+pub fn hook_settings_path(claude_dir: &Path, name: &str) -> Result<PathBuf> {
+    let lock = Lockfile::load(claude_dir)?;
+    let file = lock
+        .get(ItemKind::Hook, name)
+        .map_or(SETTINGS_FILE, LockEntry::settings_file);
+    Ok(claude_dir.join(file))
+}
+
+/// Drop an item from `<claude_dir>/registry-lock.json` (no-op if it was not recorded).
+pub fn forget(claude_dir: &Path, kind: ItemKind, name: &str) -> Result<()> {
+    if !lock_path(claude_dir).exists() {
+        return Ok(());
+    }
+    let mut lock = Lockfile::load(claude_dir)?;
+    if lock.remove(kind, name).is_some() {
+        lock.save(claude_dir)?;
+        prune_objects(claude_dir, &lock)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn hash_tree_skips_gitkeep_and_uses_relative_paths() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join("scripts")).unwrap();
+        fs::write(tmp.path().join("SKILL.md"), "hello").unwrap();
+        fs::write(tmp.path().join("scripts/run.sh"), "echo").unwrap();
+        fs::write(tmp.path().join("scripts/.gitkeep"), "").unwrap();
+
+        let files = hash_tree(tmp.path()).unwrap();
+        assert_eq!(
+            files.keys().collect::<Vec<_>>(),
+            vec!["SKILL.md", "scripts/run.sh"]
+        );
+        assert_eq!(files["SKILL.md"], hash_bytes(b"hello"));
+    }
+
+    #[test]
+    fn record_and_forget_round_trip() {
+        let tmp = TempDir::new().unwrap();
+        let claude_dir = tmp.path().join(".claude");
+        let content = tmp.path().join("content");
+        fs::create_dir_all(&content).unwrap();
+        fs::write(content.join("a.md"), "a").unwrap();
+        let files = BTreeMap::from([("a.md".to_string(), hash_bytes(b"a"))]);
+        let reg = Path::new("/reg");
+
+        let copy = InstallMode::Copy;
+        record(&claude_dir, ItemKind::Skill, "demo", reg, copy, &content, files.clone()).unwrap();
+        record(&claude_dir, ItemKind::Agent, "demo", reg, copy, &content, files).unwrap();
+        assert_eq!(load_object(&claude_dir, &hash_bytes(b"a")).unwrap(), b"a");
+
+        let lock = Lockfile::load(&claude_dir).unwrap();
+        assert_eq!(lock.items.len(), 2);
+        let entry = lock.get(ItemKind::Skill, "demo").unwrap();
+        assert_eq!(entry.source, "/reg");
+        assert_eq!(entry.cli_version, env!("CARGO_PKG_VERSION"));
+
+        forget(&claude_dir, ItemKind::Skill, "demo").unwrap();
+        let lock = Lockfile::load(&claude_dir).unwrap();
+        assert!(lock.get(ItemKind::Skill, "demo").is_none());
+        assert!(lock.get(ItemKind::Agent, "demo").is_some());
+
+        forget(&claude_dir, ItemKind::Agent, "demo").unwrap();
+        assert!(load_object(&claude_dir, &hash_bytes(b"a")).is_none());
+    }
+
+    #[test]
+    fn hook_settings_file_survives_updates() {
+        let tmp = TempDir::new().unwrap();
+        let claude_dir = tmp.path().join(".claude");
+        let reg = Path::new("/reg");
+        let files = BTreeMap::new();
+        let link = InstallMode::Link;
+
+        record(&claude_dir, ItemKind::Hook, "notify", reg, link, reg, files.clone()).unwrap();
+        let shared = claude_dir.join("settings.json");
+        assert_eq!(hook_settings_path(&claude_dir, "notify").unwrap(), shared);
+
+        set_hook_settings(&claude_dir, "notify", "settings.local.json").unwrap();
+        record(&claude_dir, ItemKind::Hook, "notify", reg, link, reg, files).unwrap();
+        let local = claude_dir.join("settings.local.json");
+        assert_eq!(hook_settings_path(&claude_dir, "notify").unwrap(), local);
+        assert!(set_hook_settings(&claude_dir, "other", "settings.json").is_err());
+    }
+}
@@ -1,9 +1,25 @@
+mod archive;
+mod audit;
+mod catalog;
 mod catalog_builder;
 mod cli;
 mod commands;
+mod config;
 mod frontmatter;
+mod git;
+mod hook_params;
+mod hook_runner;
 mod installer;
+mod jsonc;
+mod lint;
+mod lockfile;
+mod manifest;
+mod mcp;
+mod permissions;
 mod registry;
+mod settings_history;
+mod signing;
+mod transaction;
 
 use clap::Parser;
 
@@ -16,6 +32,53 @@ fn main() -> anyhow::Result<()> {
         cli::Commands::Catalog { action } => commands::catalog::run(action),
         cli::Commands::Profile { action } => commands::profile::run(action),
         cli::Commands::Hook { action } => commands::hook::run(action),
+        cli::Commands::Mcp { action } => commands::mcp::run(action),
+        cli::Commands::Settings { action } => commands::settings::run(action),
+        cli::Commands::Permissions { action } => commands::permissions::run(action),
+        cli::Commands::Source { action } => commands::source::run(action),
+        cli::Commands::Update {
+            names,
+            kind,
+            global,
+            target,
+            dry_run,
+            strategy,
+            keep_going,
+            allow_unsigned,
+            accept_risk,
+        } => {
+            let opts = installer::InstallOptions {
+                strategy,
+                dry_run,
+                link: false,
+                allow_unsigned,
+                accept_risk,
+            };
+            let claude_dir = registry::claude_dir(global, target.as_deref())?;
+            commands::update::run(&names, kind, &claude_dir, &opts, keep_going)
+        }
+        cli::Commands::Plan { global, target } => {
+            commands::plan::run(&registry::claude_dir(global, target.as_deref())?)
+        }
+        cli::Commands::Apply {
+            global,
+            target,
+            dry_run,
+            strategy,
+            keep_going,
+            allow_unsigned,
+            accept_risk,
+        } => {
+            let claude_dir = registry::claude_dir(global, target.as_deref())?;
+            commands::apply::run(&claude_dir, dry_run, strategy, keep_going, allow_unsigned, accept_risk)
+        }
+        cli::Commands::Lint => commands::lint::run(),
+        cli::Commands::Audit { name, kind } => commands::audit::run(name.as_deref(), kind),
+        cli::Commands::Sign {
+            files,
+            key,
+            generate_key,
+        } => commands::sign::run(files, key, generate_key),
         cli::Commands::Complete { r#type } => commands::complete::run(r#type),
     }
 }
@@ -0,0 +1,328 @@
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::installer;
+use crate::lockfile::{self, InstallMode, ItemKind, Lockfile};
+use crate::registry::{self, Registry};
+
+/// File name of the project manifest, stored inside the `.claude` directory.
+pub const MANIFEST_FILE: &str = "registry.toml";
+
+/// Shortest accepted prefix of a content hash pin.
+const MIN_PIN_LEN: usize = 8;
+
+/// Contents of `.claude/registry.toml`: the items a project should have.
+///
+/// ```toml
+/// skills = ["code-review", { name = "git-conventional", hash = "3f2a9c1e" }]
+/// agents = ["repo-researcher"]
+/// hooks = ["desktop-notify"]
+/// mcp = ["github"]
+/// profiles = ["web-frontend"]
+/// ```
+#[derive(Deserialize, Default, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    #[serde(default)]
+    pub skills: Vec<Declared>,
+    #[serde(default)]
+    pub agents: Vec<Declared>,
+    #[serde(default)]
+    pub hooks: Vec<Declared>,
+    #[serde(default)]
+    pub mcp: Vec<Declared>,
+    #[serde(default)]
+    pub profiles: Vec<String>,
+}
+
+/// A declared item: a bare name, or a table with an optional content hash pin.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum Declared {
+    Name(String),
+    Pinned {
+        name: String,
+        #[serde(default)]
+        hash: Option<String>,
+    },
+}
+
+impl Declared {
+    pub fn name(&self) -> &str {
+        match self {
+            Declared::Name(name) | Declared::Pinned { name, .. } => name,
+        }
+    }
+
+    pub fn hash(&self) -> Option<&str> {
+        match self {
+            Declared::Name(_) => None,
+            Declared::Pinned { hash, .. } => hash.as_deref(),
+        }
+    }
+}
+
+/// Return path to the manifest: <claude_dir>/registry.toml
+pub fn manifest_path(claude_dir: &Path) -> PathBuf {
+    claude_dir.join(MANIFEST_FILE)
+}
+
+impl Manifest {
+    /// Load the manifest from `claude_dir`.
+    pub fn load(claude_dir: &Path) -> Result<Self> {
+        let path = manifest_path(claude_dir);
+        if !path.is_file() {
+            bail!("No manifest found at {}", path.display());
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// Flatten the manifest into (kind, name) → (root, pin), expanding profiles.
+    ///
+    /// Names may be qualified as `source/name`. Items named explicitly win
+    /// over the same item pulled in by a profile.
+    fn desired(&self, reg: &Registry) -> Result<BTreeMap<(ItemKind, String), Desired>> {
+        let mut desired = BTreeMap::new();
+        for profile in &self.profiles {
+            let (source, profile) = reg.resolve_profile(profile)?;
+            let members = profile
+                .skills
+                .iter()
+                .map(|s| (ItemKind::Skill, s))
+                .chain(profile.agents.iter().map(|a| (ItemKind::Agent, a)));
+            for (kind, member) in members {
+                let (source, name) = reg.resolve_member(source, kind, member)?;
+                desired.insert((kind, name), Desired { root: source.root.clone(), pin: None });
+            }
+        }
+        let declared = [
+            (ItemKind::Skill, &self.skills),
+            (ItemKind::Agent, &self.agents),
+            (ItemKind::Hook, &self.hooks),
+            (ItemKind::Mcp, &self.mcp),
+        ];
+        for (kind, items) in declared {
+            for item in items {
+                let pin = item.hash().map(str::to_string);
+                if let Some(pin) = &pin {
+                    if pin.len() < MIN_PIN_LEN {
+                        bail!(
+                            "{} '{}': hash pin must be at least {} characters",
+                            kind,
+                            item.name(),
+                            MIN_PIN_LEN
+                        );
+                    }
+                }
+                let (source, name) = reg.resolve(kind, item.name())?;
+                desired.insert((kind, name), Desired { root: source.root.clone(), pin });
+            }
+        }
+        Ok(desired)
+    }
+}
+
+/// Where a declared item comes from and what it is pinned to.
+struct Desired {
+    root: PathBuf,
+    pin: Option<String>,
+}
+
+/// What `apply` would do to one item.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    Install,
+    Update,
+    Remove,
+    Unchanged,
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Action::Install => "+",
+            Action::Update => "~",
+            Action::Remove => "-",
+            Action::Unchanged => "=",
+        };
+        f.write_str(s)
+    }
+}
+
+/// One line of a plan.
+#[derive(Debug)]
+pub struct Change {
+    pub kind: ItemKind,
+    pub name: String,
+    pub action: Action,
+    /// Registry root providing the item (absent for removals).
+    pub root: Option<PathBuf>,
+    /// Content hash of the registry version (absent for removals).
+    pub hash: Option<String>,
+}
+
+/// Registry files of an item, keyed like the lockfile records them.
+fn registry_files(root: &Path, kind: ItemKind, name: &str) -> Result<BTreeMap<String, String>> {
+    let path = registry::item_path(root, kind, name);
+    match kind {
+        ItemKind::Skill => lockfile::hash_tree(&path),
+        ItemKind::Agent => lockfile::hash_single(&path),
+        ItemKind::Hook => lockfile::hash_hook(&path),
+        ItemKind::Mcp => lockfile::hash_tree(&path),
+    }
+}
+
+/// True if the installed copy of a recorded item is still on disk.
+fn is_present(claude_dir: &Path, kind: ItemKind, name: &str) -> bool {
+    match kind {
+        ItemKind::Skill => fs::symlink_metadata(claude_dir.join("skills").join(name)).is_ok(),
+        ItemKind::Agent => {
+            let file = format!("{}.md", name);
+            fs::symlink_metadata(claude_dir.join("agents").join(file)).is_ok()
+        }
+        ItemKind::Hook => lockfile::hook_settings_path(claude_dir, name)
+            .and_then(|settings| installer::hook_installed(name, &settings))
+            .unwrap_or(false),
+        ItemKind::Mcp => {
+            installer::mcp_installed(name, &registry::mcp_config_path(claude_dir)).unwrap_or(false)
+        }
+    }
+}
+
+/// Diff the manifest against the lockfile of `claude_dir`.
+///
+/// Fails if a declared item is missing from the registry or its content does
+/// not match the pinned hash. Only recorded items are ever removed.
+pub fn plan(reg: &Registry, claude_dir: &Path, manifest: &Manifest) -> Result<Vec<Change>> {
+    let desired = manifest.desired(reg)?;
+    let lock = Lockfile::load(claude_dir)?;
+    let mut changes = Vec::new();
+
+    for ((kind, name), Desired { root, pin }) in &desired {
+        let files = registry_files(root, *kind, name)?;
+        let hash = lockfile::content_hash(&files);
+        if let Some(pin) = pin {
+            if !hash.starts_with(pin.as_str()) {
+                bail!(
+                    "{} '{}' is pinned to {} but the registry has {}",
+                    kind,
+                    name,
+                    pin,
+                    hash
+                );
+            }
+        }
+        let action = match lock.get(*kind, name) {
+            None => Action::Install,
+            Some(_) if !is_present(claude_dir, *kind, name) => Action::Install,
+            Some(e) if e.mode == InstallMode::Link || e.files == files => Action::Unchanged,
+            Some(_) => Action::Update,
+        };
+        changes.push(Change {
+            kind: *kind,
+            name: name.clone(),
+            action,
+            root: Some(root.clone()),
+            hash: Some(hash),
+        });
+    }
+
+    for entry in &lock.items {
+        if !desired.contains_key(&(entry.kind, entry.name.clone())) {
+            changes.push(Change {
+                kind: entry.kind,
+                name: entry.name.clone(),
+                action: Action::Remove,
+                root: None,
+                hash: None,
+            });
+        }
+    }
+    Ok(changes)
+}
+
+/// Print a plan; return the number of items that would change.
+pub fn print_plan(changes: &[Change]) -> usize {
+    let count = |a: Action| changes.iter().filter(|c| c.action == a).count();
+    for c in changes.iter().filter(|c| c.action != Action::Unchanged) {
+        let hash = c.hash.as_deref().map(|h| &h[..12]).unwrap_or("");
+        let line = format!("  {} {:<6} {:<24} {}", c.action, c.kind.to_string(), c.name, hash);
+        println!("{}", line.trim_end());
+    }
+    let (install, update, remove) = (count(Action::Install), count(Action::Update), count(Action::Remove));
+    println!(
+        "\n{} to install, {} to update, {} to remove, {} unchanged.",
+        install,
+        update,
+        remove,
+        count(Action::Unchanged)
+    );
+    install + update + remove
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::Source;
+    use tempfile::TempDir;
+
+    #[test]
+    fn parses_bare_and_pinned_items() {
+        let manifest: Manifest = toml::from_str(
+            r#"
+skills = ["code-review", { name = "git-conventional", hash = "3f2a9c1e" }]
+hooks = ["desktop-notify"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(manifest.skills[0].name(), "code-review");
+        assert_eq!(manifest.skills[0].hash(), None);
+        assert_eq!(manifest.skills[1].name(), "git-conventional");
+        assert_eq!(manifest.skills[1].hash(), Some("3f2a9c1e"));
+        assert!(manifest.agents.is_empty());
+        assert!(toml::from_str::<Manifest>("skill = []").is_err());
+    }
+
+    #[test]
+    fn plan_installs_updates_and_removes() {
+        let root = TempDir::new().unwrap();
+        let project = TempDir::new().unwrap();
+        let claude_dir = project.path().join(".claude");
+        let skills = registry::skills_dir(root.path());
+        for name in ["keep", "new", "changed"] {
+            fs::create_dir_all(skills.join(name)).unwrap();
+            fs::write(skills.join(name).join("SKILL.md"), name).unwrap();
+            fs::create_dir_all(claude_dir.join("skills").join(name)).unwrap();
+        }
+        for name in ["keep", "changed", "dropped"] {
+            let files = BTreeMap::from([("SKILL.md".to_string(), lockfile::hash_bytes(b"keep"))]);
+            let copy = InstallMode::Copy;
+            lockfile::record(&claude_dir, ItemKind::Skill, name, root.path(), copy, &skills.join("keep"), files)
+                .unwrap();
+        }
+
+        let manifest: Manifest = toml::from_str(r#"skills = ["keep", "new", "changed"]"#).unwrap();
+        let reg = Registry {
+            sources: vec![Source {
+                name: "default".to_string(),
+                root: root.path().to_path_buf(),
+            }],
+        };
+        let changes = plan(&reg, &claude_dir, &manifest).unwrap();
+        let action = |name: &str| changes.iter().find(|c| c.name == name).unwrap().action;
+        assert_eq!(action("keep"), Action::Unchanged);
+        assert_eq!(action("new"), Action::Install);
+        assert_eq!(action("changed"), Action::Update);
+        assert_eq!(action("dropped"), Action::Remove);
+
+        let pinned: Manifest = toml::from_str(r#"skills = [{ name = "keep", hash = "00000000" }]"#).unwrap();
+        let err = plan(&reg, &claude_dir, &pinned).unwrap_err();
+        assert!(err.to_string().contains("pinned to 00000000"));
+    }
+}
@@ -0,0 +1,252 @@
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::sync::LazyLock;
+
+/// Server entry key naming the registry item that installed it.
+pub const OWNER_KEY: &str = "_registry_id";
+
+/// Server entry key holding the variable values given at install time.
+pub const ENV_KEY: &str = "_registry_env";
+
+/// `${NAME}` or `${NAME:-default}`, as Claude Code expands them in MCP configs.
+static PLACEHOLDER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\$\{([A-Za-z_][A-Za-z0-9_]*)(?::-[^}]*)?\}").unwrap());
+
+/// An MCP.json: server definitions in `.mcp.json` format plus registry metadata.
+#[derive(Deserialize)]
+pub struct McpDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub variables: Variables,
+    #[serde(rename = "mcpServers")]
+    pub servers: Map<String, Value>,
+}
+
+/// A `${NAME}` placeholder declared in MCP.json's `variables`.
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Variable {
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub default: Option<String>,
+    /// Never prompted for; left to the environment unless given with `--env`.
+    #[serde(default)]
+    pub secret: bool,
+}
+
+pub type Variables = BTreeMap<String, Variable>;
+
+/// Read and parse an MCP.json.
+pub fn load(path: &Path) -> Result<McpDefinition> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let def: McpDefinition = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    if def.servers.is_empty() {
+        bail!("{} defines no mcpServers", path.display());
+    }
+    Ok(def)
+}
+
+/// Names of the `${NAME}` placeholders used anywhere in `value`.
+pub fn placeholders(value: &Value) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    visit_strings(value, &mut |s| {
+        names.extend(PLACEHOLDER.captures_iter(s).map(|c| c[1].to_string()));
+    });
+    names
+}
+
+fn visit_strings(value: &Value, f: &mut impl FnMut(&str)) {
+    match value {
+        Value::String(s) => f(s),
+        Value::Array(items) => items.iter().for_each(|v| visit_strings(v, f)),
+        Value::Object(members) => members.values().for_each(|v| visit_strings(v, f)),
+        _ => {}
+    }
+}
+
+/// Values of the declared variables: `--env` pairs, then the values recorded
+/// by a previous install, then (with `prompt`) answers typed at the terminal.
+///
+/// Variables left without a value are omitted; [`substitute`] keeps their
+/// placeholder so Claude Code reads them from the environment.
+pub fn resolve_env(
+    vars: &Variables,
+    previous: Option<&Map<String, Value>>,
+    given: &[(String, String)],
+    prompt: bool,
+) -> Result<BTreeMap<String, String>> {
+    let mut env: BTreeMap<String, String> = previous
+        .into_iter()
+        .flatten()
+        .filter(|(k, _)| vars.contains_key(*k))
+        .filter_map(|(k, v)| Some((k.clone(), v.as_str()?.to_string())))
+        .collect();
+    for (key, value) in given {
+        if !vars.contains_key(key) {
+            let declared: Vec<&str> = vars.keys().map(String::as_str).collect();
+            bail!(
+                "unknown variable '{}' (declared: {})",
+                key,
+                if declared.is_empty() {
+                    "none".to_string()
+                } else {
+                    declared.join(", ")
+                }
+            );
+        }
+        env.insert(key.clone(), value.clone());
+    }
+    if !prompt {
+        return Ok(env);
+    }
+    let stdin = std::io::stdin();
+    for (name, var) in vars {
+        if var.secret || env.contains_key(name) {
+            continue;
+        }
+        let description = match var.description.as_str() {
+            "" => String::new(),
+            d => format!(" ({})", d),
+        };
+        let default = match &var.default {
+            Some(d) => format!(" [{}]", d),
+            None => " [from environment]".to_string(),
+        };
+        print!("  {}{}{}: ", name, description, default);
+        std::io::stdout().flush()?;
+        let mut answer = String::new();
+        stdin.lock().read_line(&mut answer)?;
+        let answer = answer.trim();
+        if !answer.is_empty() {
+            env.insert(name.clone(), answer.to_string());
+        }
+    }
+    Ok(env)
+}
+
+/// Replace `${NAME}` in every string of `value` with the variable's value.
+///
+/// A declared variable without a value keeps its placeholder, written as
+/// `${NAME:-default}` when it has a default. Undeclared placeholders such as
+/// `${HOME}` are left for Claude Code to expand.
+pub fn substitute(value: &mut Value, vars: &Variables, env: &BTreeMap<String, String>) {
+    match value {
+        Value::String(s) => {
+            let replaced = PLACEHOLDER.replace_all(s, |c: &regex::Captures| {
+                let (name, bare) = (&c[1], !c[0].contains(":-"));
+                match (env.get(name), vars.get(name)) {
+                    (Some(value), Some(_)) => value.clone(),
+                    (
+                        None,
+                        Some(Variable {
+                            default: Some(d), ..
+                        }),
+                    ) if bare => {
+                        format!("${{{}:-{}}}", name, d)
+                    }
+                    _ => c[0].to_string(),
+                }
+            });
+            *s = replaced.into_owned();
+        }
+        Value::Array(items) => items.iter_mut().for_each(|v| substitute(v, vars, env)),
+        Value::Object(members) => members.values_mut().for_each(|v| substitute(v, vars, env)),
+        _ => {}
+    }
+}
+
+/// `NAME=value` pairs for output. Secrets are masked; variables without a
+/// value are shown as read from the environment.
+pub fn format_env(vars: &Variables, env: &BTreeMap<String, String>) -> String {
+    vars.iter()
+        .map(|(name, var)| match env.get(name) {
+            Some(_) if var.secret => format!("{}=****", name),
+            Some(value) => format!("{}={}", name, value),
+            None => format!("{}=${{{}}} (from environment)", name, name),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// One-line summary of a server entry: `stdio <command> <args>` or `<type> <url>`.
+pub fn describe(server: &Value) -> String {
+    let field = |k| server.get(k).and_then(Value::as_str);
+    match (field("command"), field("url")) {
+        (Some(command), _) => {
+            let args = server
+                .get("args")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten();
+            let mut line = vec![command.to_string()];
+            line.extend(args.filter_map(Value::as_str).map(String::from));
+            format!("stdio {}", line.join(" "))
+        }
+        (None, Some(url)) => format!("{} {}", field("type").unwrap_or("http"), url),
+        (None, None) => "(no command or url)".to_string(),
+    }
+}
+
+/// True if a config `mcpServers` entry belongs to registry item `name`.
+pub fn owned_by(server: &Value, name: &str) -> bool {
+    server.get(OWNER_KEY).and_then(Value::as_str) == Some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn substitutes_given_values_and_keeps_the_rest_for_the_environment() {
+        let vars: Variables = serde_json::from_value(json!({
+            "DB_URL": {"description": "Connection string"},
+            "PORT": {"default": "5432"},
+            "TOKEN": {"secret": true}
+        }))
+        .unwrap();
+        let previous = json!({"DB_URL": "postgres://old", "GONE": "x"});
+        let given = [("PORT".to_string(), "6543".to_string())];
+        let env = resolve_env(&vars, previous.as_object(), &given, false).unwrap();
+        assert_eq!(env.keys().collect::<Vec<_>>(), ["DB_URL", "PORT"]);
+        assert!(resolve_env(&vars, None, &[("NOPE".into(), "1".into())], false).is_err());
+
+        let mut server = json!({
+            "command": "db-mcp",
+            "args": ["--url", "${DB_URL}", "--port=${PORT}"],
+            "env": {"TOKEN": "${TOKEN}", "HOME_DIR": "${HOME}"}
+        });
+        let mut unset = server.clone();
+        substitute(&mut unset, &vars, &BTreeMap::new());
+        assert_eq!(
+            unset["args"],
+            json!(["--url", "${DB_URL}", "--port=${PORT:-5432}"])
+        );
+        substitute(&mut server, &vars, &env);
+        assert_eq!(
+            server["args"],
+            json!(["--url", "postgres://old", "--port=6543"])
+        );
+        assert_eq!(
+            server["env"],
+            json!({"TOKEN": "${TOKEN}", "HOME_DIR": "${HOME}"})
+        );
+        let names = ["DB_URL", "HOME", "PORT", "TOKEN"].map(String::from);
+        assert_eq!(placeholders(&unset), BTreeSet::from(names));
+
+        assert_eq!(
+            format_env(&vars, &env),
+            "DB_URL=postgres://old, PORT=6543, TOKEN=${TOKEN} (from environment)"
+        );
+    }
+}
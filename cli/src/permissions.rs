@@ -0,0 +1,233 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::frontmatter::{self, AgentMeta, SkillMeta};
+use crate::jsonc::Document;
+use crate::lockfile::ItemKind;
+use crate::registry;
+
+/// Settings key recording which registry item added which permission rules.
+pub const OWNERS_KEY: &str = "_registry_permissions";
+
+/// Rule lists an item can declare, in the order they are shown.
+pub const LISTS: [&str; 2] = ["allow", "deny"];
+
+/// `permissions` declared by a skill, agent or hook.
+#[derive(Serialize, Deserialize, Default, Clone, PartialEq, Eq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Rules {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<String>,
+}
+
+impl Rules {
+    pub fn is_empty(&self) -> bool {
+        self.allow.is_empty() && self.deny.is_empty()
+    }
+
+    pub fn list(&self, list: &str) -> &[String] {
+        match list {
+            "allow" => &self.allow,
+            "deny" => &self.deny,
+            _ => &[],
+        }
+    }
+
+    fn list_mut(&mut self, list: &str) -> &mut Vec<String> {
+        match list {
+            "deny" => &mut self.deny,
+            _ => &mut self.allow,
+        }
+    }
+}
+
+/// Key of an item in `_registry_permissions`, e.g. `skill:code-review`.
+pub fn owner(kind: ItemKind, name: &str) -> String {
+    format!("{}:{}", kind, name)
+}
+
+/// The rules an item declares in its registry source. MCP servers declare none.
+pub fn declared(root: &Path, kind: ItemKind, name: &str) -> Result<Rules> {
+    let path = registry::item_path(root, kind, name);
+    let rules = match kind {
+        ItemKind::Skill => {
+            frontmatter::read::<SkillMeta>(&path.join("SKILL.md"))?
+                .unwrap_or_default()
+                .permissions
+        }
+        ItemKind::Agent => {
+            frontmatter::read::<AgentMeta>(&path)?
+                .unwrap_or_default()
+                .permissions
+        }
+        ItemKind::Hook => {
+            let hook_def = path.join("HOOK.json");
+            let content = std::fs::read_to_string(&hook_def)
+                .with_context(|| format!("Failed to read {}", hook_def.display()))?;
+            let def: Value = serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse {}", hook_def.display()))?;
+            match def.get("permissions") {
+                Some(p) => serde_json::from_value(p.clone())
+                    .with_context(|| format!("Invalid permissions in {}", hook_def.display()))?,
+                None => Rules::default(),
+            }
+        }
+        ItemKind::Mcp => Rules::default(),
+    };
+    Ok(rules)
+}
+
+/// Every owner's recorded rules.
+pub fn owners(settings: &Map<String, Value>) -> BTreeMap<String, Rules> {
+    settings
+        .get(OWNERS_KEY)
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .filter_map(|(k, v)| Some((k.clone(), serde_json::from_value(v.clone()).ok()?)))
+        .collect()
+}
+
+/// Make the rules `owner` contributes to `doc` match `rules`, returning the
+/// changes as `+allow Rule` / `-deny Rule`.
+///
+/// Only rules the item actually added are recorded as its own, so a rule the
+/// user had already written is never removed with the item. Rules another
+/// item also contributed stay until their last owner is gone.
+pub fn apply(doc: &mut Document, owner: &str, rules: &Rules) -> Result<Vec<String>> {
+    let mut all = match doc.value()? {
+        Value::Object(settings) => owners(&settings),
+        _ => bail!("settings file is not a JSON object"),
+    };
+    let previous = all.remove(owner).unwrap_or_default();
+    let mut owned = Rules::default();
+    let mut changes = Vec::new();
+    let mut removed = false;
+
+    for list in LISTS {
+        let path = ["permissions", list];
+        let present: Vec<String> = match doc.get(&path)? {
+            Some(Value::Array(items)) => items
+                .iter()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect(),
+            Some(_) => bail!("permissions.{} is not an array", list),
+            None => Vec::new(),
+        };
+        let shared = |rule: &String| all.values().any(|r| r.list(list).contains(rule));
+
+        let mut added = Vec::new();
+        for rule in rules.list(list) {
+            if owned.list(list).contains(rule) {
+                continue;
+            }
+            if !present.contains(rule) {
+                added.push(Value::from(rule.as_str()));
+                changes.push(format!("+{} {}", list, rule));
+            } else if !previous.list(list).contains(rule) && !shared(rule) {
+                // Written by the user; leave it theirs.
+                continue;
+            }
+            owned.list_mut(list).push(rule.clone());
+        }
+        if !added.is_empty() {
+            doc.push_items(&path, &added)?;
+        }
+
+        let dropped: Vec<&String> = previous
+            .list(list)
+            .iter()
+            .filter(|r| !rules.list(list).contains(r) && !shared(r) && present.contains(r))
+            .collect();
+        if !dropped.is_empty() {
+            doc.remove_items(&path, |v| {
+                v.as_str().is_some_and(|s| dropped.iter().any(|r| *r == s))
+            })?;
+            changes.extend(dropped.iter().map(|r| format!("-{} {}", list, r)));
+            removed = true;
+            if doc.get(&path)? == Some(Value::Array(Vec::new())) {
+                doc.remove(&path)?;
+            }
+        }
+    }
+    // Like hook events, lists the removal left empty are dropped.
+    if removed && doc.get(&["permissions"])? == Some(Value::Object(Map::new())) {
+        doc.remove(&["permissions"])?;
+    }
+
+    if owned != previous {
+        if owned.is_empty() {
+            doc.remove(&[OWNERS_KEY, owner])?;
+        } else {
+            doc.set(&[OWNERS_KEY, owner], &serde_json::to_value(&owned)?)?;
+        }
+        if doc.get(&[OWNERS_KEY])? == Some(Value::Object(Map::new())) {
+            doc.remove(&[OWNERS_KEY])?;
+        }
+    }
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(allow: &[&str], deny: &[&str]) -> Rules {
+        Rules {
+            allow: allow.iter().map(|s| s.to_string()).collect(),
+            deny: deny.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn tracks_ownership_of_added_rules() {
+        let original = "{\n  \"permissions\": {\n    \"allow\": [\"Bash(ls:*)\"]\n  }\n}\n";
+        let mut doc = Document::parse(original).unwrap();
+
+        let changes = apply(
+            &mut doc,
+            "skill:a",
+            &rules(&["Bash(ls:*)", "Bash(npm test:*)"], &["Read(.env)"]),
+        )
+        .unwrap();
+        assert_eq!(changes, ["+allow Bash(npm test:*)", "+deny Read(.env)"]);
+        apply(&mut doc, "hook:b", &rules(&["Bash(npm test:*)"], &[])).unwrap();
+        let settings = doc.value().unwrap();
+        assert_eq!(
+            settings["permissions"]["allow"].as_array().unwrap().len(),
+            2
+        );
+        // The user's own rule is not claimed.
+        assert_eq!(
+            settings[OWNERS_KEY]["skill:a"]["allow"],
+            serde_json::json!(["Bash(npm test:*)"])
+        );
+
+        // Re-applying is a no-op.
+        let before = doc.text().to_string();
+        assert!(apply(
+            &mut doc,
+            "skill:a",
+            &rules(&["Bash(ls:*)", "Bash(npm test:*)"], &["Read(.env)"])
+        )
+        .unwrap()
+        .is_empty());
+        assert_eq!(doc.text(), before);
+
+        // A rule shared with another owner stays until both are gone.
+        assert_eq!(
+            apply(&mut doc, "skill:a", &Rules::default()).unwrap(),
+            ["-deny Read(.env)"]
+        );
+        assert_eq!(
+            apply(&mut doc, "hook:b", &Rules::default()).unwrap(),
+            ["-allow Bash(npm test:*)"]
+        );
+        assert_eq!(doc.text(), original);
+    }
+}
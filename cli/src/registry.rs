@@ -1,23 +1,221 @@
 // implements: SPEC005
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
 use std::path::{Path, PathBuf};
 
-/// Resolve the registry root directory.
-/// Priority: CLAUDE_REGISTRY_ROOT env var > ~/.local/share/claude-registry/ > current directory.
+use crate::config::Config;
+use crate::lockfile::ItemKind;
+
+/// Name of the source used for the default root (env var, installed content or cwd).
+pub const DEFAULT_SOURCE: &str = "default";
+
+/// Resolve the primary registry root: the first source of [`Registry::load`].
+/// Priority: CLAUDE_REGISTRY_ROOT env var > first source in config.toml >
+/// ~/.local/share/claude-registry/ > current directory.
 pub fn resolve_root() -> Result<PathBuf> {
-    if let Ok(root) = std::env::var("CLAUDE_REGISTRY_ROOT") {
-        let p = PathBuf::from(root);
-        if p.is_dir() {
-            return Ok(p);
+    Ok(Registry::load()?.sources.remove(0).root)
+}
+
+/// ~/.local/share/claude-registry/, if it exists.
+fn installed_root() -> Option<PathBuf> {
+    let home = std::env::var("HOME").ok()?;
+    let installed = PathBuf::from(home).join(".local/share/claude-registry");
+    installed.is_dir().then_some(installed)
+}
+
+/// A named registry root.
+#[derive(Clone, Debug)]
+pub struct Source {
+    pub name: String,
+    pub root: PathBuf,
+}
+
+/// An item found while listing every source.
+pub struct Listed<'a> {
+    pub source: &'a Source,
+    pub path: PathBuf,
+    /// Bare name, or `source/name` when a higher-precedence source shadows it.
+    pub name: String,
+}
+
+/// Every registry root visible to the CLI, highest precedence first.
+pub struct Registry {
+    pub sources: Vec<Source>,
+}
+
+impl Registry {
+    /// Load the registry sources.
+    ///
+    /// CLAUDE_REGISTRY_ROOT, when set, is the only source. Otherwise the
+    /// sources from config.toml come first, followed by the installed content
+    /// as `default`; with neither, the current directory is used.
+    pub fn load() -> Result<Self> {
+        if let Ok(root) = std::env::var("CLAUDE_REGISTRY_ROOT") {
+            let root = PathBuf::from(root);
+            if root.is_dir() {
+                return Ok(Registry::single(root));
+            }
+        }
+        let mut sources: Vec<Source> = Config::load()?
+            .sources
+            .iter()
+            .map(|s| Source {
+                name: s.name.clone(),
+                root: s.root(),
+            })
+            .collect();
+        if let Some(installed) = installed_root() {
+            let listed = sources
+                .iter()
+                .any(|s| s.root == installed || s.name == DEFAULT_SOURCE);
+            if !listed {
+                sources.push(Source {
+                    name: DEFAULT_SOURCE.to_string(),
+                    root: installed,
+                });
+            }
+        }
+        if sources.is_empty() {
+            let cwd = std::env::current_dir().context("Failed to get current directory")?;
+            return Ok(Registry::single(cwd));
+        }
+        Ok(Registry { sources })
+    }
+
+    fn single(root: PathBuf) -> Self {
+        Registry {
+            sources: vec![Source {
+                name: DEFAULT_SOURCE.to_string(),
+                root,
+            }],
+        }
+    }
+
+    /// The highest-precedence source (where new items are created).
+    pub fn primary(&self) -> &Source {
+        &self.sources[0]
+    }
+
+    /// True if more than one source is configured (listings then show the source).
+    pub fn is_multi(&self) -> bool {
+        self.sources.len() > 1
+    }
+
+    /// The source whose root is `root`, as recorded in registry-lock.json.
+    pub fn by_root(&self, root: &str) -> Option<&Source> {
+        self.sources
+            .iter()
+            .find(|s| s.root.display().to_string() == root)
+    }
+
+    /// Find the source providing `name` (optionally qualified as `source/name`).
+    ///
+    /// Unqualified names resolve to the first source that has the item.
+    /// Returns the source and the bare item name.
+    pub fn resolve(&self, kind: ItemKind, name: &str) -> Result<(&Source, String)> {
+        match self.find(name, |root, item| item_path(root, kind, item).exists())? {
+            Some(found) => Ok(found),
+            None => bail!("{} '{}' not found in registry", kind, name),
+        }
+    }
+
+    /// Resolve a profile member: unqualified names prefer the profile's own source.
+    pub fn resolve_member<'a>(
+        &'a self,
+        profile_source: &'a Source,
+        kind: ItemKind,
+        name: &str,
+    ) -> Result<(&'a Source, String)> {
+        if !name.contains('/') && item_path(&profile_source.root, kind, name).exists() {
+            return Ok((profile_source, name.to_string()));
+        }
+        self.resolve(kind, name)
+    }
+
+    /// Like [`Registry::resolve`], for profiles.
+    pub fn resolve_profile(&self, name: &str) -> Result<(&Source, Profile)> {
+        let found = self.find(name, |root, item| {
+            profiles_dir(root).join(format!("{}.json", item)).is_file()
+        })?;
+        match found {
+            Some((source, item)) => Ok((source, load_profile(&source.root, &item)?)),
+            None => bail!("Profile '{}' not found", name),
+        }
+    }
+
+    fn find(
+        &self,
+        name: &str,
+        exists: impl Fn(&Path, &str) -> bool,
+    ) -> Result<Option<(&Source, String)>> {
+        let (source, item) = match name.split_once('/') {
+            Some((source, item)) => match self.sources.iter().find(|s| s.name == source) {
+                Some(s) => (Some(s), item),
+                None => bail!("Unknown registry source '{}' in '{}'", source, name),
+            },
+            None => (None, name),
+        };
+        let found = match source {
+            Some(s) => exists(&s.root, item).then_some(s),
+            None => self.sources.iter().find(|s| exists(&s.root, item)),
+        };
+        Ok(found.map(|s| (s, item.to_string())))
+    }
+
+    /// List items from every source with `list`, naming each with `name_of`.
+    pub fn list(
+        &self,
+        list: fn(&Path) -> Result<Vec<PathBuf>>,
+        name_of: fn(&Path) -> String,
+    ) -> Result<Vec<Listed<'_>>> {
+        let mut seen = Vec::new();
+        let mut items = Vec::new();
+        for source in &self.sources {
+            for path in list(&source.root)? {
+                let bare = name_of(&path);
+                let name = if seen.contains(&bare) {
+                    format!("{}/{}", source.name, bare)
+                } else {
+                    bare.clone()
+                };
+                seen.push(bare);
+                items.push(Listed { source, path, name });
+            }
         }
+        Ok(items)
     }
-    if let Ok(home) = std::env::var("HOME") {
-        let installed = PathBuf::from(home).join(".local/share/claude-registry");
-        if installed.is_dir() {
-            return Ok(installed);
+}
+
+impl Listed<'_> {
+    /// One `available` output line; the source is shown when several are configured.
+    pub fn row(&self, registry: &Registry, desc: &str) -> String {
+        if registry.is_multi() {
+            let source = format!("[{}]", self.source.name);
+            format!("  {:<24} {:<12} {}", self.name, source, desc)
+        } else {
+            format!("  {:<24} {}", self.name, desc)
         }
     }
-    std::env::current_dir().context("Failed to get current directory")
+}
+
+/// Name of a listed directory (skills, hooks).
+pub fn dir_name(path: &Path) -> String {
+    path.file_name().unwrap_or_default().to_string_lossy().to_string()
+}
+
+/// Name of a listed file without its extension (agents, profiles).
+pub fn file_stem(path: &Path) -> String {
+    path.file_stem().unwrap_or_default().to_string_lossy().to_string()
+}
+
+/// Registry path of an item: the skill, hook or MCP directory, or the agent file.
+pub fn item_path(root: &Path, kind: ItemKind, name: &str) -> PathBuf {
+    match kind {
+        ItemKind::Skill => skills_dir(root).join(name),
+        ItemKind::Agent => agents_dir(root).join(format!("{}.md", name)),
+        ItemKind::Hook => hooks_dir(root).join(name),
+        ItemKind::Mcp => mcp_dir(root).join(name),
+    }
 }
 
 /// Return path to skills directory: <root>/claude/skills
@@ -68,7 +266,7 @@ pub fn list_agents(root: &Path) -> Result<Vec<PathBuf>> {
     for entry in std::fs::read_dir(&dir)? {
         let entry = entry?;
         let path = entry.path();
-        if path.is_file() && path.extension().map_or(false, |e| e == "md") {
+        if path.is_file() && path.extension().is_some_and(|e| e == "md") {
             results.push(path);
         }
     }
@@ -99,18 +297,181 @@ pub fn list_hooks(root: &Path) -> Result<Vec<PathBuf>> {
     Ok(results)
 }
 
-/// Resolve settings.json path.
-/// global=true → ~/.claude/settings.json
-/// target=Some(p) → p/.claude/settings.json
-pub fn settings_path(global: bool, target: Option<&Path>) -> anyhow::Result<std::path::PathBuf> {
+/// Return path to MCP server definitions: <root>/claude/mcp
+pub fn mcp_dir(root: &Path) -> PathBuf {
+    root.join("claude").join("mcp")
+}
+
+/// List MCP directories (each containing MCP.json)
+pub fn list_mcp(root: &Path) -> Result<Vec<PathBuf>> {
+    let dir = mcp_dir(root);
+    if !dir.is_dir() {
+        return Ok(vec![]);
+    }
+    let mut results = Vec::new();
+    for entry in std::fs::read_dir(&dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.is_dir() && path.join("MCP.json").is_file() {
+            results.push(path);
+        }
+    }
+    results.sort();
+    Ok(results)
+}
+
+/// Resolve the .claude directory items are installed into.
+/// global=true → ~/.claude
+/// target=Some(p) → p/.claude
+pub fn claude_dir(global: bool, target: Option<&Path>) -> Result<PathBuf> {
     if global {
         let home = std::env::var("HOME").context("HOME environment variable not set")?;
-        Ok(PathBuf::from(home).join(".claude").join("settings.json"))
+        Ok(PathBuf::from(home).join(".claude"))
     } else if let Some(t) = target {
-        Ok(t.join(".claude").join("settings.json"))
+        Ok(t.join(".claude"))
+    } else {
+        bail!("Either --global or --target must be specified")
+    }
+}
+
+/// Resolve settings.json path.
+/// global=true → ~/.claude/settings.json
+/// target=Some(p) → p/.claude/settings.json
+pub fn settings_path(global: bool, target: Option<&Path>) -> Result<PathBuf> {
+    Ok(claude_dir(global, target)?.join("settings.json"))
+}
+
+/// The MCP config file servers are installed into for `claude_dir`.
+/// ~/.claude → ~/.claude.json (user scope)
+/// <p>/.claude → <p>/.mcp.json (project scope)
+pub fn mcp_config_path(claude_dir: &Path) -> PathBuf {
+    let parent = claude_dir.parent().unwrap_or(Path::new("."));
+    match std::env::var_os("HOME") {
+        Some(home) if parent == Path::new(&home) => parent.join(".claude.json"),
+        _ => parent.join(".mcp.json"),
+    }
+}
+
+/// A settings file Claude Code reads hooks from.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, clap::ValueEnum)]
+pub enum SettingsScope {
+    /// Enterprise managed settings (read-only)
+    Managed,
+    /// ~/.claude/settings.json
+    User,
+    /// <target>/.claude/settings.json, shared with the team
+    Project,
+    /// <target>/.claude/settings.local.json, git-ignored
+    Local,
+}
+
+impl SettingsScope {
+    /// Scopes in Claude Code's precedence order, highest first.
+    pub const PRECEDENCE: [SettingsScope; 4] = [
+        SettingsScope::Managed,
+        SettingsScope::Local,
+        SettingsScope::Project,
+        SettingsScope::User,
+    ];
+}
+
+impl std::fmt::Display for SettingsScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            SettingsScope::Managed => "managed",
+            SettingsScope::User => "user",
+            SettingsScope::Project => "project",
+            SettingsScope::Local => "local",
+        })
+    }
+}
+
+/// Resolve the settings file of a hook command.
+/// Without --scope, --global means user and --target means project.
+pub fn scoped_settings_path(
+    scope: Option<SettingsScope>,
+    global: bool,
+    target: Option<&Path>,
+) -> Result<PathBuf> {
+    match scope {
+        Some(scope) => scope_settings_path(scope, target),
+        None => settings_path(global, target),
+    }
+}
+
+/// The settings file of `scope`; project and local need the project path.
+pub fn scope_settings_path(scope: SettingsScope, target: Option<&Path>) -> Result<PathBuf> {
+    match (scope, target) {
+        (SettingsScope::Managed, None) => managed_settings_path(),
+        (SettingsScope::User, None) => settings_path(true, None),
+        (SettingsScope::Managed | SettingsScope::User, Some(_)) => {
+            bail!("--scope {} cannot be combined with --target", scope)
+        }
+        (SettingsScope::Project, Some(t)) => Ok(t.join(".claude").join("settings.json")),
+        (SettingsScope::Local, Some(t)) => Ok(t.join(".claude").join("settings.local.json")),
+        (_, None) => bail!("--scope {} needs --target <path>", scope),
+    }
+}
+
+/// Enterprise managed settings file.
+/// Priority: CLAUDE_REGISTRY_MANAGED_SETTINGS env var > the platform's system path
+pub fn managed_settings_path() -> Result<PathBuf> {
+    if let Ok(path) = std::env::var("CLAUDE_REGISTRY_MANAGED_SETTINGS") {
+        return Ok(PathBuf::from(path));
+    }
+    let path = if cfg!(target_os = "macos") {
+        "/Library/Application Support/ClaudeCode/managed-settings.json"
+    } else if cfg!(windows) {
+        r"C:\Program Files\ClaudeCode\managed-settings.json"
     } else {
-        anyhow::bail!("Either --global or --target must be specified")
+        "/etc/claude-code/managed-settings.json"
+    };
+    Ok(PathBuf::from(path))
+}
+
+/// A profile: a named bundle of skills and agents (profiles/<name>.json).
+#[derive(Deserialize)]
+pub struct Profile {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub skills: Vec<String>,
+    #[serde(default)]
+    pub agents: Vec<String>,
+}
+
+/// Load profiles/<name>.json
+pub fn load_profile(root: &Path, name: &str) -> Result<Profile> {
+    let path = profiles_dir(root).join(format!("{}.json", name));
+    if !path.is_file() {
+        bail!("Profile '{}' not found", name);
+    }
+    read_profile(&path)
+}
+
+/// Parse a profile JSON file
+pub fn read_profile(path: &Path) -> Result<Profile> {
+    let content = std::fs::read_to_string(path)?;
+    serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// List profile JSON files
+pub fn list_profiles(root: &Path) -> Result<Vec<PathBuf>> {
+    let dir = profiles_dir(root);
+    if !dir.is_dir() {
+        return Ok(vec![]);
     }
+    let mut results = Vec::new();
+    for entry in std::fs::read_dir(&dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.is_file() && path.extension().is_some_and(|e| e == "json") {
+            results.push(path);
+        }
+    }
+    results.sort();
+    Ok(results)
 }
 
 #[cfg(test)]
@@ -139,6 +500,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn scopes_resolve_to_settings_files() {
+        let project = Path::new("/p");
+        let path = |scope| scope_settings_path(scope, Some(project));
+        let shared = project.join(".claude/settings.json");
+        assert_eq!(path(SettingsScope::Project).unwrap(), shared);
+        let local = project.join(".claude/settings.local.json");
+        assert_eq!(path(SettingsScope::Local).unwrap(), local);
+        assert!(path(SettingsScope::User).is_err());
+        assert!(scope_settings_path(SettingsScope::Local, None).is_err());
+        assert_eq!(scoped_settings_path(None, false, Some(project)).unwrap(), shared);
+    }
+
     #[test]
     fn resolve_root_env_var_takes_priority_over_installed() {
         let tmp = TempDir::new().unwrap();
@@ -155,22 +529,38 @@ mod tests {
 
         std::env::remove_var("CLAUDE_REGISTRY_ROOT");
     }
-}
 
-/// List profile JSON files
-pub fn list_profiles(root: &Path) -> Result<Vec<PathBuf>> {
-    let dir = profiles_dir(root);
-    if !dir.is_dir() {
-        return Ok(vec![]);
-    }
-    let mut results = Vec::new();
-    for entry in std::fs::read_dir(&dir)? {
-        let entry = entry?;
-        let path = entry.path();
-        if path.is_file() && path.extension().map_or(false, |e| e == "json") {
-            results.push(path);
+    #[test]
+    fn registry_resolves_by_precedence_and_qualified_name() {
+        let tmp = TempDir::new().unwrap();
+        let source = |name: &str| Source {
+            name: name.to_string(),
+            root: tmp.path().join(name),
+        };
+        let reg = Registry {
+            sources: vec![source("internal"), source("public")],
+        };
+        for (root, skill) in [("internal", "code-review"), ("public", "code-review"), ("public", "lint")] {
+            let dir = skills_dir(&tmp.path().join(root)).join(skill);
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join("SKILL.md"), skill).unwrap();
         }
+
+        let (found, name) = reg.resolve(ItemKind::Skill, "code-review").unwrap();
+        assert_eq!((found.name.as_str(), name.as_str()), ("internal", "code-review"));
+        let (found, _) = reg.resolve(ItemKind::Skill, "public/code-review").unwrap();
+        assert_eq!(found.name, "public");
+        let (found, _) = reg.resolve(ItemKind::Skill, "lint").unwrap();
+        assert_eq!(found.name, "public");
+        assert!(reg.resolve(ItemKind::Skill, "internal/lint").is_err());
+        assert!(reg.resolve(ItemKind::Skill, "other/lint").is_err());
+
+        let names: Vec<String> = reg
+            .list(list_skills, dir_name)
+            .unwrap()
+            .into_iter()
+            .map(|l| l.name)
+            .collect();
+        assert_eq!(names, ["code-review", "public/code-review", "lint"]);
     }
-    results.sort();
-    Ok(results)
 }
@@ -0,0 +1,195 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config;
+
+/// Snapshots kept per settings file; older ones are pruned.
+const KEEP: usize = 50;
+
+/// A settings file as it was before a registry command changed it.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Snapshot {
+    pub id: String,
+    pub path: PathBuf,
+    pub created_at: String,
+    /// The command that was about to change the file.
+    pub action: String,
+    /// File content; `None` if the file did not exist.
+    pub content: Option<String>,
+}
+
+/// `<state dir>/settings-history`, one `<id>.json` per snapshot.
+pub fn history_dir() -> Result<PathBuf> {
+    Ok(config::state_dir()?.join("settings-history"))
+}
+
+/// The file snapshot `id` is stored in.
+pub fn snapshot_file(id: &str) -> Result<PathBuf> {
+    Ok(history_dir()?.join(format!("{}.json", id)))
+}
+
+fn absolute(path: &Path) -> Result<PathBuf> {
+    std::path::absolute(path).with_context(|| format!("Failed to resolve {}", path.display()))
+}
+
+/// Snapshot `path` (its current content, or its absence) before `action` changes it.
+pub fn record(path: &Path, action: &str) -> Result<Snapshot> {
+    record_in(&history_dir()?, path, action)
+}
+
+fn record_in(dir: &Path, path: &Path, action: &str) -> Result<Snapshot> {
+    let content = match path.exists() {
+        true => Some(
+            fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?,
+        ),
+        false => None,
+    };
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let now = chrono::Utc::now();
+    let stamp = now.format("%Y%m%d-%H%M%S").to_string();
+    let id = (1..)
+        .map(|n| match n {
+            1 => stamp.clone(),
+            n => format!("{}-{}", stamp, n),
+        })
+        .find(|id| !dir.join(format!("{}.json", id)).exists())
+        .expect("unbounded range");
+    let snapshot = Snapshot {
+        id,
+        path: absolute(path)?,
+        created_at: now.to_rfc3339_opts(chrono::SecondsFormat::Nanos, true),
+        action: action.to_string(),
+        content,
+    };
+    let file = dir.join(format!("{}.json", snapshot.id));
+    fs::write(&file, serde_json::to_string_pretty(&snapshot)? + "\n")
+        .with_context(|| format!("Failed to write {}", file.display()))?;
+
+    let mut older: Vec<Snapshot> = list_in(dir, Some(&snapshot.path))?;
+    for old in older.drain(..).skip(KEEP) {
+        let _ = fs::remove_file(dir.join(format!("{}.json", old.id)));
+    }
+    Ok(snapshot)
+}
+
+/// Snapshots newest first, optionally only those of settings file `path`.
+pub fn list(path: Option<&Path>) -> Result<Vec<Snapshot>> {
+    let path = path.map(absolute).transpose()?;
+    list_in(&history_dir()?, path.as_deref())
+}
+
+fn list_in(dir: &Path, path: Option<&Path>) -> Result<Vec<Snapshot>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut snapshots = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let file = entry?.path();
+        if file.extension().is_none_or(|e| e != "json") {
+            continue;
+        }
+        let content = fs::read_to_string(&file)?;
+        let snapshot: Snapshot = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", file.display()))?;
+        if path.is_none_or(|p| p == snapshot.path) {
+            snapshots.push(snapshot);
+        }
+    }
+    snapshots.sort_by(|a, b| {
+        let key = |s: &Snapshot| (s.created_at.clone(), sequence(&s.id));
+        key(b).cmp(&key(a))
+    });
+    Ok(snapshots)
+}
+
+/// The counter of a snapshot id: 1 for `<stamp>`, n for `<stamp>-<n>`.
+fn sequence(id: &str) -> u32 {
+    id.splitn(3, '-')
+        .nth(2)
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(1)
+}
+
+/// True if settings file `path` did not exist before its oldest snapshot,
+/// i.e. a registry command created it.
+pub fn created_by_registry(path: &Path) -> Result<bool> {
+    Ok(list(Some(path))?.last().is_some_and(|s| s.content.is_none()))
+}
+
+pub fn load(id: &str) -> Result<Snapshot> {
+    load_in(&history_dir()?, id)
+}
+
+fn load_in(dir: &Path, id: &str) -> Result<Snapshot> {
+    let file = dir.join(format!("{}.json", id));
+    if id.contains(['/', '\\']) || !file.is_file() {
+        bail!(
+            "No settings snapshot '{}' (see `claude-registry settings history`)",
+            id
+        );
+    }
+    let content = fs::read_to_string(&file)?;
+    serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", file.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn records_lists_and_prunes_snapshots() {
+        let tmp = TempDir::new().unwrap();
+        let dir = tmp.path().join("history");
+        let settings = tmp.path().join("settings.json");
+        let other = tmp.path().join("settings.local.json");
+
+        let first = record_in(&dir, &settings, "hook install a").unwrap();
+        assert_eq!(first.content, None);
+        fs::write(&settings, "{}\n").unwrap();
+        let second = record_in(&dir, &settings, "hook install b").unwrap();
+        assert_ne!(first.id, second.id);
+        record_in(&dir, &other, "hook install c").unwrap();
+
+        let listed = list_in(&dir, Some(&settings)).unwrap();
+        let ids: Vec<&str> = listed.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, [second.id.as_str(), first.id.as_str()]);
+        assert_eq!(list_in(&dir, None).unwrap().len(), 3);
+        assert_eq!(
+            load_in(&dir, &second.id).unwrap().content.as_deref(),
+            Some("{}\n")
+        );
+        assert!(load_in(&dir, "missing").is_err());
+
+        for _ in 0..KEEP {
+            record_in(&dir, &settings, "again").unwrap();
+        }
+        assert_eq!(list_in(&dir, Some(&settings)).unwrap().len(), KEEP);
+        assert_eq!(list_in(&dir, Some(&other)).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn snapshots_taken_together_list_by_counter() {
+        let tmp = TempDir::new().unwrap();
+        for id in ["20260101-120000", "20260101-120000-2", "20260101-120000-10"] {
+            let snapshot = Snapshot {
+                id: id.to_string(),
+                path: tmp.path().join("settings.json"),
+                created_at: "2026-01-01T12:00:00.000000000Z".to_string(),
+                action: "hook install a".to_string(),
+                content: None,
+            };
+            let json = serde_json::to_string(&snapshot).unwrap();
+            fs::write(tmp.path().join(format!("{}.json", id)), json).unwrap();
+        }
+        let listed = list_in(tmp.path(), None).unwrap();
+        let ids: Vec<&str> = listed.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            ["20260101-120000-10", "20260101-120000-2", "20260101-120000"]
+        );
+    }
+}
@@ -0,0 +1,328 @@
+use anyhow::{bail, Context, Result};
+use ed25519_dalek::{Signer, SigningKey, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::catalog;
+use crate::config::{self, Config};
+use crate::lockfile::{self, ItemKind};
+use crate::registry;
+
+/// Contents of a detached `<file>.sig`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Signature {
+    pub algorithm: String,
+    /// Public key of the signer (hex).
+    pub key: String,
+    /// Ed25519 signature over the file's bytes (hex).
+    pub signature: String,
+}
+
+const ALGORITHM: &str = "ed25519";
+
+/// Where the detached signature of `file` lives.
+pub fn signature_path(file: &Path) -> PathBuf {
+    let mut path = file.as_os_str().to_owned();
+    path.push(".sig");
+    PathBuf::from(path)
+}
+
+/// Default signing key: `signing.key` next to config.toml.
+pub fn default_key_path() -> Result<PathBuf> {
+    let config = config::config_path()?;
+    Ok(config
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join("signing.key"))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex<const N: usize>(hex: &str) -> Option<[u8; N]> {
+    let hex = hex.trim();
+    if hex.len() != N * 2 || !hex.is_ascii() {
+        return None;
+    }
+    let mut bytes = [0u8; N];
+    for (i, b) in bytes.iter_mut().enumerate() {
+        *b = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(bytes)
+}
+
+pub fn public_key_hex(key: &VerifyingKey) -> String {
+    to_hex(key.as_bytes())
+}
+
+pub fn parse_public_key(hex: &str) -> Result<VerifyingKey> {
+    let bytes = from_hex::<32>(hex)
+        .with_context(|| format!("'{}' is not a hex ed25519 public key", hex))?;
+    VerifyingKey::from_bytes(&bytes)
+        .with_context(|| format!("'{}' is not a valid ed25519 public key", hex))
+}
+
+/// Create a new signing key at `path`, refusing to overwrite an existing one.
+pub fn generate_key(path: &Path) -> Result<VerifyingKey> {
+    if path.exists() {
+        bail!("{} already exists", path.display());
+    }
+    let mut seed = [0u8; 32];
+    getrandom::fill(&mut seed).map_err(|e| anyhow::anyhow!("Failed to generate a key: {}", e))?;
+    let key = SigningKey::from_bytes(&seed);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    write_private(path, &format!("{}\n", to_hex(&seed)))?;
+    Ok(key.verifying_key())
+}
+
+#[cfg(unix)]
+fn write_private(path: &Path, content: &str) -> Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    file.write_all(content.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &Path, content: &str) -> Result<()> {
+    fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+pub fn load_key(path: &Path) -> Result<SigningKey> {
+    let content = fs::read_to_string(path).with_context(|| {
+        format!(
+            "Failed to read signing key {} (create one with `sign --generate-key`)",
+            path.display()
+        )
+    })?;
+    let seed = from_hex::<32>(&content)
+        .with_context(|| format!("{} is not a hex ed25519 key", path.display()))?;
+    Ok(SigningKey::from_bytes(&seed))
+}
+
+/// Sign `file` with `key`, writing `<file>.sig`.
+pub fn sign_file(key: &SigningKey, file: &Path) -> Result<PathBuf> {
+    let bytes = fs::read(file).with_context(|| format!("Failed to read {}", file.display()))?;
+    let sig = Signature {
+        algorithm: ALGORITHM.to_string(),
+        key: public_key_hex(&key.verifying_key()),
+        signature: to_hex(&key.sign(&bytes).to_bytes()),
+    };
+    let path = signature_path(file);
+    fs::write(&path, format!("{}\n", serde_json::to_string_pretty(&sig)?))
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Check that `file` carries a valid signature by one of `trusted`.
+pub fn verify_file(file: &Path, trusted: &[VerifyingKey]) -> Result<()> {
+    let path = signature_path(file);
+    let Ok(content) = fs::read_to_string(&path) else {
+        bail!(
+            "{} is not signed ({} not found)",
+            file.display(),
+            path.display()
+        );
+    };
+    let sig: Signature = serde_json::from_str(&content)
+        .with_context(|| format!("Invalid signature file {}", path.display()))?;
+    if sig.algorithm != ALGORITHM {
+        bail!(
+            "{}: unsupported algorithm '{}'",
+            path.display(),
+            sig.algorithm
+        );
+    }
+    let Some(key) = trusted
+        .iter()
+        .find(|k| public_key_hex(k) == sig.key.trim().to_lowercase())
+    else {
+        bail!(
+            "{} is signed by an untrusted key {}",
+            file.display(),
+            sig.key
+        );
+    };
+    let signature = from_hex::<64>(&sig.signature)
+        .map(|b| ed25519_dalek::Signature::from_bytes(&b))
+        .with_context(|| format!("{}: signature is not 64 hex bytes", path.display()))?;
+    let bytes = fs::read(file).with_context(|| format!("Failed to read {}", file.display()))?;
+    if key.verify_strict(&bytes, &signature).is_err() {
+        bail!(
+            "{} does not match its signature (modified after signing?)",
+            file.display()
+        );
+    }
+    Ok(())
+}
+
+/// The trusted keys from config.toml; empty means signatures are not enforced.
+fn trusted_keys() -> Result<Vec<VerifyingKey>> {
+    Config::load()?
+        .trusted_keys
+        .iter()
+        .map(|k| parse_public_key(k))
+        .collect()
+}
+
+/// Turn a verification failure into a warning when `allow_unsigned` is set.
+fn enforce(what: &str, result: Result<()>, allow_unsigned: bool) -> Result<()> {
+    match result {
+        Ok(()) => Ok(()),
+        Err(e) if allow_unsigned => {
+            eprintln!("  Warning: installing unverified {}: {:#}", what, e);
+            Ok(())
+        }
+        Err(e) => Err(e.context(format!(
+            "Refusing to install {} without a trusted signature (pass --allow-unsigned to install anyway)",
+            what
+        ))),
+    }
+}
+
+/// Verify a signed catalog or archive before installing from it.
+pub fn check_file(file: &Path, allow_unsigned: bool) -> Result<()> {
+    let trusted = trusted_keys()?;
+    if trusted.is_empty() {
+        return Ok(());
+    }
+    enforce(
+        &file.display().to_string(),
+        verify_file(file, &trusted),
+        allow_unsigned,
+    )
+}
+
+/// Verify a registry item before installing it: the registry's catalog for
+/// `kind` must be signed by a trusted key and list the item with the hash of
+/// `files`.
+pub fn check_item(
+    root: &Path,
+    kind: ItemKind,
+    name: &str,
+    files: &BTreeMap<String, String>,
+    allow_unsigned: bool,
+) -> Result<()> {
+    let trusted = trusted_keys()?;
+    if trusted.is_empty() {
+        return Ok(());
+    }
+    let result = signed_hash(root, kind, name, &trusted).and_then(|(path, hash)| {
+        if hash != lockfile::content_hash(files) {
+            bail!(
+                "{} '{}' does not match the hash in {}",
+                kind,
+                name,
+                path.display()
+            );
+        }
+        Ok(())
+    });
+    enforce(&format!("{} '{}'", kind, name), result, allow_unsigned)
+}
+
+/// The hash a verified catalog under `root` records for item `name`.
+fn signed_hash(
+    root: &Path,
+    kind: ItemKind,
+    name: &str,
+    trusted: &[VerifyingKey],
+) -> Result<(PathBuf, String)> {
+    let path = root.join(catalog::file_name(kind));
+    verify_file(&path, trusted)?;
+    let content = fs::read_to_string(&path)?;
+    let catalog: Value = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse catalog {}", path.display()))?;
+    let entry = catalog
+        .get("items")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .find(|e| {
+            let field = |k| e.get(k).and_then(Value::as_str);
+            match kind {
+                ItemKind::Agent => {
+                    field("file").is_some_and(|f| registry::file_stem(Path::new(f)) == name)
+                }
+                _ => field("dir") == Some(name),
+            }
+        });
+    match entry.and_then(|e| e.get("hash")).and_then(Value::as_str) {
+        Some(hash) => Ok((path, hash.to_string())),
+        None => bail!("{} '{}' is not listed in {}", kind, name, path.display()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn sign_and_verify_detect_tampering() {
+        let tmp = TempDir::new().unwrap();
+        let key_path = tmp.path().join("keys/signing.key");
+        let public = generate_key(&key_path).unwrap();
+        assert!(generate_key(&key_path).is_err());
+        let key = load_key(&key_path).unwrap();
+        assert_eq!(key.verifying_key(), public);
+        assert_eq!(parse_public_key(&public_key_hex(&public)).unwrap(), public);
+
+        let file = tmp.path().join("skill-catalog.json");
+        fs::write(&file, "{\"items\": []}\n").unwrap();
+        assert!(verify_file(&file, &[public])
+            .unwrap_err()
+            .to_string()
+            .contains("not signed"));
+
+        assert_eq!(
+            sign_file(&key, &file).unwrap(),
+            tmp.path().join("skill-catalog.json.sig")
+        );
+        verify_file(&file, &[public]).unwrap();
+
+        let other = SigningKey::from_bytes(&[7u8; 32]).verifying_key();
+        assert!(verify_file(&file, &[other])
+            .unwrap_err()
+            .to_string()
+            .contains("untrusted"));
+
+        fs::write(&file, "{\"items\": [{}]}\n").unwrap();
+        assert!(verify_file(&file, &[public])
+            .unwrap_err()
+            .to_string()
+            .contains("does not match"));
+    }
+
+    #[test]
+    fn signed_hash_finds_items_by_install_name() {
+        let tmp = TempDir::new().unwrap();
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        let catalog = tmp.path().join("agent-catalog.json");
+        fs::write(
+            &catalog,
+            r#"{"version": "x", "items": [{"name": "Reviewer", "file": "reviewer.md", "hash": "abc"}]}"#,
+        )
+        .unwrap();
+        sign_file(&key, &catalog).unwrap();
+
+        let trusted = [key.verifying_key()];
+        let (_, hash) = signed_hash(tmp.path(), ItemKind::Agent, "reviewer", &trusted).unwrap();
+        assert_eq!(hash, "abc");
+        assert!(signed_hash(tmp.path(), ItemKind::Agent, "other", &trusted).is_err());
+        assert!(signed_hash(tmp.path(), ItemKind::Skill, "reviewer", &trusted).is_err());
+    }
+}
@@ -0,0 +1,322 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Return `<parent>/.<file name>.<tag>-<pid>`, a sibling path used for staging and backups.
+fn sibling(path: &Path, tag: &str) -> PathBuf {
+    let name = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    path.with_file_name(format!(".{}.{}-{}", name, tag, std::process::id()))
+}
+
+/// Remove a file, symlink or directory tree if it exists.
+pub fn remove_path(path: &Path) -> Result<()> {
+    let meta = match fs::symlink_metadata(path) {
+        Ok(m) => m,
+        Err(_) => return Ok(()),
+    };
+    if meta.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+    .with_context(|| format!("Failed to remove {}", path.display()))
+}
+
+/// Copy a file, symlink or directory tree verbatim (symlinks are recreated, not followed).
+fn copy_path(src: &Path, dst: &Path) -> Result<()> {
+    for entry in WalkDir::new(src).follow_root_links(false) {
+        let entry = entry?;
+        let rel = entry.path().strip_prefix(src)?;
+        let target = if rel.as_os_str().is_empty() {
+            dst.to_path_buf()
+        } else {
+            dst.join(rel)
+        };
+        if entry.path_is_symlink() {
+            let link = fs::read_link(entry.path())?;
+            create_symlink(&link, &target)?;
+        } else if entry.file_type().is_dir() {
+            fs::create_dir_all(&target)?;
+        } else {
+            fs::copy(entry.path(), &target)
+                .with_context(|| format!("Failed to copy {}", entry.path().display()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Create a symlink at `path` pointing to `original`.
+#[cfg(unix)]
+pub fn create_symlink(original: &Path, path: &Path) -> Result<()> {
+    std::os::unix::fs::symlink(original, path)
+        .with_context(|| format!("Failed to create link {}", path.display()))
+}
+
+/// Create a symlink at `path` pointing to `original`.
+#[cfg(windows)]
+pub fn create_symlink(original: &Path, path: &Path) -> Result<()> {
+    let result = if original.is_dir() {
+        std::os::windows::fs::symlink_dir(original, path)
+    } else {
+        std::os::windows::fs::symlink_file(original, path)
+    };
+    result.with_context(|| format!("Failed to create link {}", path.display()))
+}
+
+/// True if `path` itself is a symlink (without following it).
+pub fn is_symlink(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_symlink())
+}
+
+/// Build a directory in a staging area next to `dst`, then swap it into place.
+///
+/// The staging directory starts as a copy of `seed` (if given). If `build`
+/// fails the staging directory is discarded and `dst` is left untouched.
+pub fn stage_dir<T>(
+    dst: &Path,
+    seed: Option<&Path>,
+    build: impl FnOnce(&Path) -> Result<T>,
+) -> Result<T> {
+    if let Some(parent) = dst.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    let staging = sibling(dst, "staging");
+    remove_path(&staging)?;
+    match seed {
+        Some(seed) => copy_path(seed, &staging)?,
+        None => fs::create_dir_all(&staging)?,
+    }
+
+    let value = match build(&staging) {
+        Ok(v) => v,
+        Err(e) => {
+            remove_path(&staging)?;
+            return Err(e);
+        }
+    };
+
+    let old = sibling(dst, "old");
+    let had_old = fs::symlink_metadata(dst).is_ok();
+    if had_old {
+        remove_path(&old)?;
+        fs::rename(dst, &old).with_context(|| format!("Failed to move {}", dst.display()))?;
+    }
+    if let Err(e) = fs::rename(&staging, dst) {
+        if had_old {
+            fs::rename(&old, dst)?;
+        }
+        remove_path(&staging)?;
+        return Err(e).with_context(|| format!("Failed to move staged files to {}", dst.display()));
+    }
+    if had_old {
+        remove_path(&old)?;
+    }
+    Ok(value)
+}
+
+/// Write a file via a temporary sibling and rename, so readers never see a partial file.
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    let tmp = sibling(path, "tmp");
+    fs::write(&tmp, contents).with_context(|| format!("Failed to write {}", tmp.display()))?;
+    fs::rename(&tmp, path).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Copy a file via a temporary sibling and rename.
+pub fn copy_atomic(src: &Path, dst: &Path) -> Result<()> {
+    let bytes = fs::read(src).with_context(|| format!("Failed to read {}", src.display()))?;
+    write_atomic(dst, bytes)
+}
+
+/// A set of filesystem changes that can be undone as a unit.
+///
+/// Every path is backed up by [`Transaction::track`] before it is first
+/// modified; [`Transaction::rollback`] restores all of them in reverse order.
+#[derive(Default)]
+pub struct Transaction {
+    tracked: Vec<(PathBuf, Option<PathBuf>)>,
+}
+
+impl Transaction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember the current state of `path` (which may not exist yet).
+    pub fn track(&mut self, path: &Path) -> Result<()> {
+        if self.tracked.iter().any(|(p, _)| p == path) {
+            return Ok(());
+        }
+        let backup = if fs::symlink_metadata(path).is_ok() {
+            let backup = sibling(path, "rollback");
+            remove_path(&backup)?;
+            copy_path(path, &backup)
+                .with_context(|| format!("Failed to back up {}", path.display()))?;
+            Some(backup)
+        } else {
+            None
+        };
+        self.tracked.push((path.to_path_buf(), backup));
+        Ok(())
+    }
+
+    /// Remember that `path` did not exist before it was just created, so a
+    /// rollback removes it.
+    pub fn track_created(&mut self, path: &Path) {
+        if !self.tracked.iter().any(|(p, _)| p == path) {
+            self.tracked.push((path.to_path_buf(), None));
+        }
+    }
+
+    /// Number of paths that would be restored by a rollback.
+    pub fn tracked_count(&self) -> usize {
+        self.tracked.len()
+    }
+
+    /// Keep all changes and delete the backups.
+    pub fn commit(self) -> Result<()> {
+        for (_, backup) in &self.tracked {
+            if let Some(b) = backup {
+                remove_path(b)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Restore every tracked path to the state it had when it was tracked.
+    pub fn rollback(self) -> Result<()> {
+        for (path, backup) in self.tracked.iter().rev() {
+            remove_path(path)?;
+            if let Some(b) = backup {
+                fs::rename(b, path)
+                    .with_context(|| format!("Failed to restore {}", path.display()))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Apply `install` to every item inside a transaction.
+///
+/// By default the first failure rolls back every item applied so far and is
+/// returned. With `keep_going` each item gets its own transaction: a failing
+/// item is rolled back, a warning is printed and the rest are still applied.
+pub fn apply_all<T>(
+    items: &[T],
+    keep_going: bool,
+    mut install: impl FnMut(&mut Transaction, &T) -> Result<()>,
+) -> Result<()> {
+    if keep_going {
+        for item in items {
+            let mut tx = Transaction::new();
+            match install(&mut tx, item) {
+                Ok(()) => tx.commit()?,
+                Err(e) => {
+                    tx.rollback()?;
+                    eprintln!("  Warning: {}", e);
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    let mut tx = Transaction::new();
+    for item in items {
+        if let Err(e) = install(&mut tx, item) {
+            let n = tx.tracked_count();
+            tx.rollback()?;
+            let hint = match items.len() {
+                1 => "",
+                _ => " (use --keep-going to skip failing items)",
+            };
+            return Err(e.context(format!("Aborted, rolled back {} path(s){}", n, hint)));
+        }
+    }
+    tx.commit()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::bail;
+    use tempfile::TempDir;
+
+    #[test]
+    fn stage_dir_leaves_destination_untouched_on_failure() {
+        let tmp = TempDir::new().unwrap();
+        let dst = tmp.path().join("skill");
+        fs::create_dir_all(&dst).unwrap();
+        fs::write(dst.join("SKILL.md"), "old").unwrap();
+
+        let result: Result<()> = stage_dir(&dst, Some(&dst), |stage| {
+            fs::write(stage.join("SKILL.md"), "new")?;
+            bail!("copy failed")
+        });
+        assert!(result.is_err());
+        assert_eq!(fs::read_to_string(dst.join("SKILL.md")).unwrap(), "old");
+        assert_eq!(fs::read_dir(tmp.path()).unwrap().count(), 1);
+
+        stage_dir(&dst, None, |stage| Ok(fs::write(stage.join("SKILL.md"), "new")?)).unwrap();
+        assert_eq!(fs::read_to_string(dst.join("SKILL.md")).unwrap(), "new");
+        assert_eq!(fs::read_dir(tmp.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn apply_all_rolls_back_every_item_on_failure() {
+        let tmp = TempDir::new().unwrap();
+        let existing = tmp.path().join("existing.md");
+        fs::write(&existing, "old").unwrap();
+        fs::create_dir_all(tmp.path().join("dir")).unwrap();
+        fs::write(tmp.path().join("dir/file.md"), "old").unwrap();
+
+        let items = ["existing.md", "dir", "new.md", "fail"];
+        let result = apply_all(&items, false, |tx, name| {
+            if *name == "fail" {
+                bail!("boom");
+            }
+            let path = tmp.path().join(name);
+            tx.track(&path)?;
+            if path.is_dir() {
+                fs::remove_dir_all(&path)?;
+            } else {
+                fs::write(&path, "new")?;
+            }
+            Ok(())
+        });
+
+        let err = result.unwrap_err().to_string();
+        assert_eq!(err, "Aborted, rolled back 3 path(s) (use --keep-going to skip failing items)");
+        assert_eq!(fs::read_to_string(&existing).unwrap(), "old");
+        assert_eq!(fs::read_to_string(tmp.path().join("dir/file.md")).unwrap(), "old");
+        assert!(!tmp.path().join("new.md").exists());
+        assert_eq!(fs::read_dir(tmp.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn apply_all_keep_going_applies_remaining_items() {
+        let tmp = TempDir::new().unwrap();
+        let items = ["a.md", "fail", "b.md"];
+        apply_all(&items, true, |tx, name| {
+            if *name == "fail" {
+                bail!("boom");
+            }
+            let path = tmp.path().join(name);
+            tx.track(&path)?;
+            fs::write(&path, "new")?;
+            Ok(())
+        })
+        .unwrap();
+
+        assert!(tmp.path().join("a.md").is_file());
+        assert!(tmp.path().join("b.md").is_file());
+    }
+}
@@ -5,20 +5,25 @@ _claude_registry() {
     local cur prev words cword
     _init_completion || return
 
-    local top_commands="skill agent catalog profile hook"
+    local top_commands="skill agent catalog profile hook mcp settings permissions source update plan apply lint audit sign"
 
     # Determine position context
     local cmd="" subcmd=""
     local i
     for ((i = 1; i < cword; i++)); do
-        case "${words[i]}" in
-            skill|agent|catalog|profile|hook)
-                cmd="${words[i]}"
-                ;;
-            install|list|new|available|uninstall|build)
-                subcmd="${words[i]}"
-                ;;
-        esac
+        if [[ -z "$cmd" ]]; then
+            case "${words[i]}" in
+                skill|agent|catalog|profile|hook|mcp|settings|permissions|source|update|plan|apply|lint|audit|sign)
+                    cmd="${words[i]}"
+                    ;;
+            esac
+        else
+            case "${words[i]}" in
+                install|list|new|available|uninstall|pack|build|check|add|update|remove|history|restore)
+                    subcmd="${words[i]}"
+                    ;;
+            esac
+        fi
     done
 
     # Top-level completion
@@ -27,59 +32,207 @@ _claude_registry() {
         return
     fi
 
+    # update takes item names directly (no sub-action)
+    if [[ "$cmd" == "update" ]]; then
+        case "$prev" in
+            --kind)
+                COMPREPLY=($(compgen -W "skill agent hook mcp" -- "$cur"))
+                return
+                ;;
+            --strategy)
+                COMPREPLY=($(compgen -W "keep theirs merge" -- "$cur"))
+                return
+                ;;
+            --target)
+                _filedir -d
+                return
+                ;;
+        esac
+        if [[ "$cur" == -* ]]; then
+            COMPREPLY=($(compgen -W "--kind --global --target --dry-run --strategy --keep-going --allow-unsigned --accept-risk --help" -- "$cur"))
+        else
+            local candidates
+            candidates="$(claude-registry _complete skills 2>/dev/null) $(claude-registry _complete agents 2>/dev/null) $(claude-registry _complete hooks 2>/dev/null) $(claude-registry _complete mcp 2>/dev/null)"
+            COMPREPLY=($(compgen -W "$candidates" -- "$cur"))
+        fi
+        return
+    fi
+
+    # plan / apply take options only
+    if [[ "$cmd" == "plan" || "$cmd" == "apply" ]]; then
+        case "$prev" in
+            --strategy)
+                COMPREPLY=($(compgen -W "keep theirs merge" -- "$cur"))
+                return
+                ;;
+            --target)
+                _filedir -d
+                return
+                ;;
+        esac
+        if [[ "$cmd" == "plan" ]]; then
+            COMPREPLY=($(compgen -W "--global --target --help" -- "$cur"))
+        else
+            COMPREPLY=($(compgen -W "--global --target --dry-run --strategy --keep-going --allow-unsigned --accept-risk --help" -- "$cur"))
+        fi
+        return
+    fi
+
+    # audit takes an optional skill, hook or MCP server name
+    if [[ "$cmd" == "audit" ]]; then
+        if [[ "$prev" == "--kind" ]]; then
+            COMPREPLY=($(compgen -W "skill hook mcp" -- "$cur"))
+        elif [[ "$cur" == -* ]]; then
+            COMPREPLY=($(compgen -W "--kind --help" -- "$cur"))
+        else
+            local candidates
+            candidates="$(claude-registry _complete skills 2>/dev/null) $(claude-registry _complete hooks 2>/dev/null) $(claude-registry _complete mcp 2>/dev/null)"
+            COMPREPLY=($(compgen -W "$candidates" -- "$cur"))
+        fi
+        return
+    fi
+
+    # sign takes files to sign
+    if [[ "$cmd" == "sign" ]]; then
+        if [[ "$cur" == -* ]]; then
+            COMPREPLY=($(compgen -W "--key --generate-key --help" -- "$cur"))
+        else
+            _filedir
+        fi
+        return
+    fi
+
+    # lint takes no arguments
+    if [[ "$cmd" == "lint" ]]; then
+        COMPREPLY=($(compgen -W "--help" -- "$cur"))
+        return
+    fi
+
     # Sub-action completion
     if [[ -z "$subcmd" ]]; then
         case "$cmd" in
             skill)
-                COMPREPLY=($(compgen -W "install list new available uninstall" -- "$cur"))
+                COMPREPLY=($(compgen -W "install list new available uninstall pack" -- "$cur"))
                 ;;
             agent)
                 COMPREPLY=($(compgen -W "install list available uninstall" -- "$cur"))
                 ;;
             catalog)
-                COMPREPLY=($(compgen -W "build" -- "$cur"))
+                COMPREPLY=($(compgen -W "build check" -- "$cur"))
                 ;;
             profile)
                 COMPREPLY=($(compgen -W "install list" -- "$cur"))
                 ;;
             hook)
-                COMPREPLY=($(compgen -W "install uninstall list available new" -- "$cur"))
+                COMPREPLY=($(compgen -W "install uninstall list available test new" -- "$cur"))
+                ;;
+            mcp)
+                COMPREPLY=($(compgen -W "install uninstall list available" -- "$cur"))
+                ;;
+            settings)
+                COMPREPLY=($(compgen -W "history restore" -- "$cur"))
+                ;;
+            permissions)
+                COMPREPLY=($(compgen -W "list" -- "$cur"))
+                ;;
+            source)
+                COMPREPLY=($(compgen -W "add update list remove" -- "$cur"))
                 ;;
         esac
         return
     fi
 
+    if [[ "$prev" == "--strategy" ]]; then
+        COMPREPLY=($(compgen -W "keep theirs merge" -- "$cur"))
+        return
+    fi
+    if [[ "$prev" == "--catalog" ]]; then
+        _filedir json
+        return
+    fi
+    if [[ "$prev" == "--event" ]]; then
+        COMPREPLY=($(compgen -W "PreToolUse PermissionRequest PostToolUse PostToolUseFailure Notification UserPromptSubmit Stop SubagentStart SubagentStop PreCompact SessionStart SessionEnd" -- "$cur"))
+        return
+    fi
+    if [[ "$prev" == "--from-archive" || "$prev" == "--output" || "$prev" == "-o" || "$prev" == "--payload" ]]; then
+        _filedir
+        return
+    fi
+    if [[ "$prev" == "--scope" ]]; then
+        if [[ "$cmd" == "hook" && "$subcmd" != "list" ]]; then
+            COMPREPLY=($(compgen -W "user project local" -- "$cur"))
+        else
+            COMPREPLY=($(compgen -W "managed user project local" -- "$cur"))
+        fi
+        return
+    fi
+    # --set, --env and --tool take free-form values
+    if [[ "$prev" == "--set" || "$prev" == "--env" || "$prev" == "--tool" ]]; then
+        return
+    fi
+
     # Option completion
     if [[ "$cur" == -* ]]; then
         case "$cmd" in
             skill)
                 case "$subcmd" in
-                    install)  COMPREPLY=($(compgen -W "--target --dry-run --help" -- "$cur")) ;;
-                    list)     COMPREPLY=($(compgen -W "--target --help" -- "$cur")) ;;
+                    install)  COMPREPLY=($(compgen -W "--global --target --dry-run --strategy --keep-going --link --catalog --from-archive --allow-unsigned --accept-risk --help" -- "$cur")) ;;
+                    pack)     COMPREPLY=($(compgen -W "--output --help" -- "$cur")) ;;
+                    list)     COMPREPLY=($(compgen -W "--global --target --help" -- "$cur")) ;;
                     new)      COMPREPLY=($(compgen -W "--description --help" -- "$cur")) ;;
-                    uninstall) COMPREPLY=($(compgen -W "--target --help" -- "$cur")) ;;
+                    uninstall) COMPREPLY=($(compgen -W "--global --target --help" -- "$cur")) ;;
                 esac
                 ;;
             agent)
                 case "$subcmd" in
-                    install)  COMPREPLY=($(compgen -W "--target --dry-run --help" -- "$cur")) ;;
-                    list)     COMPREPLY=($(compgen -W "--target --help" -- "$cur")) ;;
-                    uninstall) COMPREPLY=($(compgen -W "--target --help" -- "$cur")) ;;
+                    install)  COMPREPLY=($(compgen -W "--global --target --dry-run --strategy --keep-going --link --catalog --allow-unsigned --help" -- "$cur")) ;;
+                    list)     COMPREPLY=($(compgen -W "--global --target --help" -- "$cur")) ;;
+                    uninstall) COMPREPLY=($(compgen -W "--global --target --help" -- "$cur")) ;;
                 esac
                 ;;
             profile)
                 case "$subcmd" in
-                    install)  COMPREPLY=($(compgen -W "--target --dry-run --help" -- "$cur")) ;;
+                    install)  COMPREPLY=($(compgen -W "--global --target --dry-run --strategy --keep-going --allow-unsigned --accept-risk --help" -- "$cur")) ;;
                 esac
                 ;;
             hook)
                 case "$subcmd" in
-                    install)  COMPREPLY=($(compgen -W "--global --target --dry-run --help" -- "$cur")) ;;
-                    uninstall) COMPREPLY=($(compgen -W "--global --target --help" -- "$cur")) ;;
-                    list)     COMPREPLY=($(compgen -W "--global --target --help" -- "$cur")) ;;
+                    install)  COMPREPLY=($(compgen -W "--global --target --scope --dry-run --allow-unsigned --accept-risk --set --help" -- "$cur")) ;;
+                    uninstall) COMPREPLY=($(compgen -W "--global --target --scope --help" -- "$cur")) ;;
+                    list)     COMPREPLY=($(compgen -W "--global --target --scope --effective --help" -- "$cur")) ;;
+                    test)     COMPREPLY=($(compgen -W "--event --payload --tool --set --help" -- "$cur")) ;;
                     new)      COMPREPLY=($(compgen -W "--description --help" -- "$cur")) ;;
                 esac
                 ;;
+            mcp)
+                case "$subcmd" in
+                    install)  COMPREPLY=($(compgen -W "--global --target --dry-run --allow-unsigned --accept-risk --env --help" -- "$cur")) ;;
+                    uninstall|list) COMPREPLY=($(compgen -W "--global --target --help" -- "$cur")) ;;
+                esac
+                ;;
+            permissions)
+                case "$subcmd" in
+                    list)     COMPREPLY=($(compgen -W "--global --target --scope --help" -- "$cur")) ;;
+                esac
+                ;;
+            settings)
+                case "$subcmd" in
+                    history)  COMPREPLY=($(compgen -W "--global --target --scope --help" -- "$cur")) ;;
+                    restore)  COMPREPLY=($(compgen -W "--dry-run --help" -- "$cur")) ;;
+                esac
+                ;;
+            catalog)
+                case "$subcmd" in
+                    build)    COMPREPLY=($(compgen -W "--timestamp --help" -- "$cur")) ;;
+                    *)        COMPREPLY=($(compgen -W "--help" -- "$cur")) ;;
+                esac
+                ;;
+            source)
+                case "$subcmd" in
+                    add)      COMPREPLY=($(compgen -W "--ref --help" -- "$cur")) ;;
+                    *)        COMPREPLY=($(compgen -W "--help" -- "$cur")) ;;
+                esac
+                ;;
         esac
         return
     fi
@@ -88,7 +241,7 @@ _claude_registry() {
     case "$cmd" in
         skill)
             case "$subcmd" in
-                install|uninstall)
+                install|uninstall|pack)
                     local candidates
                     candidates=$(claude-registry _complete skills 2>/dev/null)
                     COMPREPLY=($(compgen -W "$candidates" -- "$cur"))
@@ -115,13 +268,40 @@ _claude_registry() {
             ;;
         hook)
             case "$subcmd" in
-                install|uninstall)
+                install|uninstall|test)
                     local candidates
                     candidates=$(claude-registry _complete hooks 2>/dev/null)
                     COMPREPLY=($(compgen -W "$candidates" -- "$cur"))
                     ;;
             esac
             ;;
+        mcp)
+            case "$subcmd" in
+                install|uninstall)
+                    local candidates
+                    candidates=$(claude-registry _complete mcp 2>/dev/null)
+                    COMPREPLY=($(compgen -W "$candidates" -- "$cur"))
+                    ;;
+            esac
+            ;;
+        settings)
+            case "$subcmd" in
+                restore)
+                    local candidates
+                    candidates=$(claude-registry _complete snapshots 2>/dev/null)
+                    COMPREPLY=($(compgen -W "$candidates" -- "$cur"))
+                    ;;
+            esac
+            ;;
+        source)
+            case "$subcmd" in
+                update|remove)
+                    local candidates
+                    candidates=$(claude-registry _complete sources 2>/dev/null)
+                    COMPREPLY=($(compgen -W "$candidates" -- "$cur"))
+                    ;;
+            esac
+            ;;
     esac
 }
 